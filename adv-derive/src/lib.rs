@@ -0,0 +1,255 @@
+//! The `AocParse` derive, re-exported from `adv::parse`.
+//!
+//! Only the code generation lives here. Matching input against a pattern is
+//! done at runtime by `adv::parse::derive`, so the expanded code stays small.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{
+    parse_macro_input, spanned::Spanned, Attribute, Data, DeriveInput, Error, Fields, Ident,
+    LitStr, Result,
+};
+
+/// Implement `FromStr` by matching input against a pattern.
+///
+/// A struct, and each variant of an enum, takes a pattern naming its fields
+/// in braces, like `#[aoc(pattern = "{left}   {right}")]`. Every field must
+/// appear once, and two fields must be split by some literal text. Use `{{`
+/// and `}}` for literal braces.
+///
+/// Each field is parsed with its own `FromStr`, so fields can be other
+/// derived types. A `Vec` field marked `#[aoc(sep = ",")]` is split on the
+/// separator and each item parsed in turn.
+///
+/// Enum variants are tried in order, and the first to match is used.
+#[proc_macro_derive(AocParse, attributes(aoc))]
+pub fn derive_aoc_parse(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let pattern = pattern(&input.attrs, input.ident.span())?;
+            let build = construct(quote!(Self), &data.fields, &pattern)?;
+            quote!(#build)
+        }
+        Data::Enum(data) => {
+            let attempts = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let ident = &variant.ident;
+                    let pattern = pattern(&variant.attrs, ident.span())?;
+                    let build = construct(quote!(Self::#ident), &variant.fields, &pattern)?;
+                    Ok(quote! {
+                        let attempt = (|| -> ::std::result::Result<Self, ::adv::parse::ParseError> {
+                            #build
+                        })();
+                        match attempt {
+                            ::std::result::Result::Ok(parsed) => {
+                                return ::std::result::Result::Ok(parsed)
+                            }
+                            ::std::result::Result::Err(e) => errors.push(e),
+                        }
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let type_name = name.to_string();
+
+            quote! {
+                let mut errors = ::std::vec::Vec::new();
+                #(#attempts)*
+                ::std::result::Result::Err(::adv::parse::derive::no_variant(input, #type_name, errors))
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new(
+                name.span(),
+                "AocParse can only be derived for structs and enums",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::std::str::FromStr for #name #ty_generics #where_clause {
+            type Err = ::adv::parse::ParseError;
+
+            fn from_str(input: &str) -> ::std::result::Result<Self, Self::Err> {
+                #body
+            }
+        }
+    })
+}
+
+enum Segment {
+    Literal(String),
+    Field(String),
+}
+
+/// The segments of the `pattern` given in `attrs`, which must be there.
+fn pattern(attrs: &[Attribute], span: Span) -> Result<Vec<Segment>> {
+    let mut pattern = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("aoc")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("pattern") {
+                let lit: LitStr = meta.value()?.parse()?;
+                pattern = Some(segments(&lit)?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `pattern = \"...\"`"))
+            }
+        })?;
+    }
+
+    pattern.ok_or_else(|| Error::new(span, "missing #[aoc(pattern = \"...\")]"))
+}
+
+fn segments(lit: &LitStr) -> Result<Vec<Segment>> {
+    let text = lit.value();
+    let mut segments = vec![];
+    let mut literal = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                let field: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                if field.is_empty() {
+                    return Err(Error::new(lit.span(), "empty field name in pattern"));
+                }
+                if !literal.is_empty() {
+                    segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                } else if matches!(segments.last(), Some(Segment::Field(_))) {
+                    return Err(Error::new(
+                        lit.span(),
+                        format!(
+                            "field `{}` needs some text between it and the one before",
+                            field
+                        ),
+                    ));
+                }
+                segments.push(Segment::Field(field));
+            }
+            '}' => return Err(Error::new(lit.span(), "unmatched `}` in pattern, use `}}`")),
+            c => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        segments.push(Segment::Literal(literal));
+    }
+
+    Ok(segments)
+}
+
+/// The separator given as `#[aoc(sep = "...")]` on a field, if any.
+fn separator(attrs: &[Attribute]) -> Result<Option<LitStr>> {
+    let mut sep = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("aoc")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("sep") {
+                sep = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `sep = \"...\"`"))
+            }
+        })?;
+    }
+
+    Ok(sep)
+}
+
+/// Code that matches `input` against `pattern` and builds `path` from the
+/// captures.
+fn construct(path: TokenStream2, fields: &Fields, pattern: &[Segment]) -> Result<TokenStream2> {
+    let named: Vec<_> = match fields {
+        Fields::Named(fields) => fields.named.iter().collect(),
+        Fields::Unit => vec![],
+        Fields::Unnamed(_) => {
+            return Err(Error::new(
+                fields.span(),
+                "AocParse needs named fields to refer to in the pattern",
+            ))
+        }
+    };
+
+    let captured: Vec<&str> = pattern
+        .iter()
+        .filter_map(|segment| match segment {
+            Segment::Field(name) => Some(name.as_str()),
+            Segment::Literal(_) => None,
+        })
+        .collect();
+
+    for name in &captured {
+        if !named
+            .iter()
+            .any(|field| field.ident.as_ref().unwrap() == name)
+        {
+            return Err(Error::new(
+                fields.span(),
+                format!("pattern refers to unknown field `{}`", name),
+            ));
+        }
+        if captured.iter().filter(|other| other == &name).count() > 1 {
+            return Err(Error::new(
+                fields.span(),
+                format!("field `{}` appears more than once in the pattern", name),
+            ));
+        }
+    }
+
+    let values = named
+        .iter()
+        .map(|field| {
+            let ident: &Ident = field.ident.as_ref().unwrap();
+            let name = ident.to_string();
+            let idx = captured.iter().position(|c| *c == name).ok_or_else(|| {
+                Error::new(
+                    ident.span(),
+                    format!("field `{}` is not in the pattern", name),
+                )
+            })?;
+
+            Ok(match separator(&field.attrs)? {
+                Some(sep) => quote! {
+                    #ident: ::adv::parse::derive::list(input, #name, captures[#idx], #sep)?
+                },
+                None => quote! {
+                    #ident: ::adv::parse::derive::field(input, #name, captures[#idx])?
+                },
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let segments = pattern.iter().map(|segment| match segment {
+        Segment::Literal(text) => quote!(::adv::parse::derive::Segment::Literal(#text)),
+        Segment::Field(name) => quote!(::adv::parse::derive::Segment::Field(#name)),
+    });
+    let body = if named.is_empty() {
+        quote!(#path)
+    } else {
+        quote!(#path { #(#values),* })
+    };
+
+    Ok(quote! {
+        #[allow(unused_variables)]
+        let captures = ::adv::parse::derive::captures(input, &[#(#segments),*])?;
+        ::std::result::Result::Ok(#body)
+    })
+}
@@ -0,0 +1,143 @@
+//! Compares the streaming scanner for day 3 with the regex it replaced.
+//!
+//! Run with `cargo bench --bench day_03`.
+
+use adv::solns::day_03;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+/// Day 3 as it was before the scanner, unchanged apart from making the two
+/// parts public.
+mod baseline {
+    use adv::parse::{self, ParseError};
+    use regex::Regex;
+
+    pub fn part1(puzzle: &str) -> anyhow::Result<i32> {
+        let re = Regex::new(r"mul\(([0-9]*),([0-9]*)\)")?;
+
+        let mut total = 0;
+        for (_, [a, b]) in re.captures_iter(puzzle).map(|c| c.extract()) {
+            total += operand(puzzle, a)? * operand(puzzle, b)?;
+        }
+
+        Ok(total)
+    }
+
+    pub fn part2(puzzle: &str) -> anyhow::Result<i32> {
+        let re = Regex::new(r"mul\(([0-9]*),([0-9]*)\)|do\(\)|don't\(\)")?;
+
+        let mut total = 0;
+        let mut is_active = true;
+        for cap in re.captures_iter(puzzle) {
+            match parse_op(&cap.get(0).ok_or(anyhow::anyhow!("No match found."))?)? {
+                Operation::Do => is_active = true,
+                Operation::Dont => is_active = false,
+                Operation::Mul => {
+                    let a = cap
+                        .get(1)
+                        .ok_or(anyhow::anyhow!("Multiply op does not have first value."))?
+                        .as_str();
+                    let b = cap
+                        .get(2)
+                        .ok_or(anyhow::anyhow!("Multiple op does not have second value."))?
+                        .as_str();
+                    if is_active {
+                        total += operand(puzzle, a)? * operand(puzzle, b)?;
+                    }
+                }
+            }
+        }
+
+        Ok(total)
+    }
+
+    enum Operation {
+        Do,
+        Dont,
+        Mul,
+    }
+
+    fn parse_op(raw_op: &regex::Match) -> anyhow::Result<Operation> {
+        if raw_op.as_str() == "do()" {
+            return Ok(Operation::Do);
+        } else if raw_op.as_str() == "don't()" {
+            return Ok(Operation::Dont);
+        } else if raw_op.as_str().contains("mul") {
+            return Ok(Operation::Mul);
+        }
+
+        Err(anyhow::anyhow!(
+            "Operation cannot be categorized {}",
+            raw_op.as_str()
+        ))
+    }
+
+    /// Parse one side of a `mul`, located within the whole puzzle if it fails.
+    fn operand(puzzle: &str, text: &str) -> Result<i32, ParseError> {
+        parse::value(text).map_err(|e| e.within(puzzle, text))
+    }
+}
+
+/// About as much corrupted memory as a real puzzle input, made from a mix of
+/// valid instructions and the near misses around them.
+///
+/// The baseline errors on a `mul` with an empty operand and allows more than
+/// three digits, so none of the pieces can join up into either.
+fn memory() -> String {
+    const PIECES: &[&str] = &[
+        "mul(",
+        "mul(12,",
+        "mul(7,345)",
+        "mul (2,4)",
+        "do()",
+        "don't()",
+        "don't(",
+        "what()",
+        "#!$@",
+        "mul[3,7]",
+        "}from()",
+        "mul(2,4]",
+        "mul(44,8)",
+    ];
+
+    // A fixed linear congruential generator keeps the input the same between runs.
+    let mut seed: u64 = 3;
+    let mut memory = String::new();
+    while memory.len() < 20_000 {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        memory.push_str(PIECES[(seed >> 33) as usize % PIECES.len()]);
+    }
+
+    memory
+}
+
+fn scanner_vs_regex(c: &mut Criterion) {
+    let memory = memory();
+    assert_eq!(
+        day_03::sum_reader(memory.as_bytes(), false).unwrap(),
+        baseline::part1(&memory).unwrap() as u64
+    );
+    assert_eq!(
+        day_03::sum_reader(memory.as_bytes(), true).unwrap(),
+        baseline::part2(&memory).unwrap() as u64
+    );
+
+    let mut group = c.benchmark_group("day_03");
+    group.bench_function("scanner part 1", |b| {
+        b.iter(|| day_03::sum_reader(black_box(memory.as_bytes()), false).unwrap())
+    });
+    group.bench_function("regex part 1", |b| {
+        b.iter(|| baseline::part1(black_box(&memory)).unwrap())
+    });
+    group.bench_function("scanner part 2", |b| {
+        b.iter(|| day_03::sum_reader(black_box(memory.as_bytes()), true).unwrap())
+    });
+    group.bench_function("regex part 2", |b| {
+        b.iter(|| baseline::part2(black_box(&memory)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, scanner_vs_regex);
+criterion_main!(benches);
@@ -0,0 +1,59 @@
+use std::{env, fmt::Write, fs, path::Path};
+
+// Scans `src/solns` for `day_XX.rs` files and generates their module
+// declarations along with the registry used to dispatch to them, so a new
+// day is picked up without editing `solns/mod.rs`. Days whose `dayXX` feature
+// is disabled are registered as compiled out rather than declared.
+fn main() {
+    println!("cargo:rerun-if-changed=src/solns");
+
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let solns_dir = Path::new(&manifest_dir).join("src").join("solns");
+
+    let mut days = vec![];
+    for entry in fs::read_dir(&solns_dir).unwrap() {
+        let path = entry.unwrap().path();
+        let file_name = path.file_name().unwrap().to_string_lossy().to_string();
+        if file_name == "mod.rs" {
+            continue;
+        }
+
+        match parse_day(&file_name) {
+            Some(day) => days.push((day, path)),
+            None => println!(
+                "cargo:warning=Ignoring src/solns/{}, solutions must be named day_XX.rs",
+                file_name
+            ),
+        }
+    }
+    days.sort();
+
+    let mut generated = String::new();
+    for (day, path) in days.iter().filter(|(day, _)| is_enabled(*day)) {
+        writeln!(generated, "#[path = {:?}]", path.display().to_string()).unwrap();
+        writeln!(generated, "pub mod day_{:02};", day).unwrap();
+    }
+
+    writeln!(
+        generated,
+        "\npub const SOLUTIONS: &[(u8, Option<Solution>)] = &["
+    )
+    .unwrap();
+    for (day, _) in &days {
+        if is_enabled(*day) {
+            writeln!(generated, "    ({}, Some(day_{:02}::run)),", day, day).unwrap();
+        } else {
+            writeln!(generated, "    ({}, None),", day).unwrap();
+        }
+    }
+    writeln!(generated, "];").unwrap();
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("solns.rs"), generated).unwrap();
+}
+
+include!("src/day_file.rs");
+
+fn is_enabled(day: u8) -> bool {
+    env::var_os(format!("CARGO_FEATURE_DAY{:02}", day)).is_some()
+}
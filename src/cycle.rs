@@ -0,0 +1,190 @@
+//! Running simulations until they repeat.
+//!
+//! A simulation is a start state and a step function giving the state after
+//! it, or `None` once the simulation has ended.
+
+use std::{collections::HashMap, hash::Hash};
+
+/// Where a simulation starts repeating itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// The step at which the first repeated state is reached.
+    pub start: usize,
+    /// How many steps it takes to come back round to the same state.
+    pub len: usize,
+}
+
+impl Cycle {
+    /// Which step before the cycle ends, or in its first lap, is in the same
+    /// state as step `n`.
+    pub fn equivalent_step(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.len
+        }
+    }
+}
+
+/// How a simulation turned out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome<S> {
+    /// The simulation ended after `steps` steps, in state `last`.
+    Ended {
+        steps: usize,
+        last: S,
+    },
+    Cycled(Cycle),
+}
+
+impl<S> Outcome<S> {
+    pub fn is_cycle(&self) -> bool {
+        matches!(self, Outcome::Cycled(_))
+    }
+}
+
+/// Run a simulation until it ends or reaches a state it has been in before,
+/// remembering every state seen.
+pub fn find_cycle<S>(start: S, mut step: impl FnMut(&S) -> Option<S>) -> Outcome<S>
+where
+    S: Clone + Eq + Hash,
+{
+    let mut seen = HashMap::new();
+    let mut state = start;
+    for steps in 0.. {
+        if let Some(&first) = seen.get(&state) {
+            return Outcome::Cycled(Cycle {
+                start: first,
+                len: steps - first,
+            });
+        }
+
+        match step(&state) {
+            Some(next) => {
+                seen.insert(state, steps);
+                state = next;
+            }
+            None => return Outcome::Ended { steps, last: state },
+        }
+    }
+
+    unreachable!("a simulation cannot outlast usize steps")
+}
+
+/// Like [`find_cycle`], using Brent's algorithm to only keep two states at a
+/// time, at the cost of calling `step` a few more times.
+///
+/// `step` must be deterministic, as the states are replayed from the start.
+pub fn brent<S: Clone + Eq>(start: S, mut step: impl FnMut(&S) -> Option<S>) -> Outcome<S> {
+    // Find the length by moving the tortoise up to the hare at every power of
+    // two, until the hare laps round to it.
+    let (mut power, mut len) = (1, 1);
+    let mut tortoise = start.clone();
+    let mut hare = start.clone();
+    let mut steps = 0;
+    loop {
+        hare = match step(&hare) {
+            Some(next) => next,
+            None => return Outcome::Ended { steps, last: hare },
+        };
+        steps += 1;
+
+        if tortoise == hare {
+            break;
+        }
+        if power == len {
+            tortoise = hare.clone();
+            power *= 2;
+            len = 0;
+        }
+        len += 1;
+    }
+
+    // Then walk two states `len` apart from the start until they meet.
+    let mut tortoise = start.clone();
+    let mut hare = start;
+    for _ in 0..len {
+        hare = step(&hare).expect("a replayed simulation cycles again");
+    }
+    let mut cycle_start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise).expect("a replayed simulation cycles again");
+        hare = step(&hare).expect("a replayed simulation cycles again");
+        cycle_start += 1;
+    }
+
+    Outcome::Cycled(Cycle {
+        start: cycle_start,
+        len,
+    })
+}
+
+/// The state after `n` steps, skipping whole laps once the simulation
+/// cycles, or `None` if it ends first.
+pub fn nth<S>(start: S, mut step: impl FnMut(&S) -> Option<S>, n: usize) -> Option<S>
+where
+    S: Clone + Eq + Hash,
+{
+    let mut history = vec![];
+    let mut seen = HashMap::new();
+    let mut state = start;
+    for steps in 0..n {
+        if let Some(&first) = seen.get(&state) {
+            let cycle = Cycle {
+                start: first,
+                len: steps - first,
+            };
+            return Some(history.swap_remove(cycle.equivalent_step(n)));
+        }
+
+        let next = step(&state)?;
+        seen.insert(state.clone(), steps);
+        history.push(state);
+        state = next;
+    }
+
+    Some(state)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Counts up to 10 then drops back to 7, so cycles 7, 8, 9, 10.
+    fn looping(n: &u32) -> Option<u32> {
+        Some(if *n < 10 { n + 1 } else { n - 3 })
+    }
+
+    fn ending(n: &u32) -> Option<u32> {
+        (*n < 5).then_some(n + 1)
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let cycle = Cycle { start: 7, len: 4 };
+        assert_eq!(find_cycle(0, looping), Outcome::Cycled(cycle));
+        assert_eq!(brent(0, looping), Outcome::Cycled(cycle));
+        assert_eq!(
+            brent(8, looping),
+            Outcome::Cycled(Cycle { start: 0, len: 4 })
+        );
+        assert_eq!(
+            brent(0, |_| Some(0)),
+            Outcome::Cycled(Cycle { start: 0, len: 1 })
+        );
+
+        let ended = Outcome::Ended { steps: 5, last: 5 };
+        assert_eq!(find_cycle(0, ending), ended);
+        assert_eq!(brent(0, ending), ended);
+    }
+
+    #[test]
+    fn test_nth() {
+        assert_eq!(nth(0, looping, 3), Some(3));
+        assert_eq!(nth(0, looping, 11), Some(7));
+        assert_eq!(nth(0, looping, 1_000_000_000), Some(8));
+        assert_eq!(nth(0, ending, 5), Some(5));
+        assert_eq!(nth(0, ending, 6), None);
+        assert_eq!(Cycle { start: 7, len: 4 }.equivalent_step(5), 5);
+    }
+}
@@ -0,0 +1,13 @@
+// Shared by build.rs and `adv doctor` through `include!`, so that both agree
+// on which files are solutions. It must not use anything from the crate.
+
+/// The day a solution file is for, if it is named `day_XX.rs` with a two
+/// digit day from 01 to 25.
+fn parse_day(file_name: &str) -> Option<u8> {
+    let digits = file_name.strip_prefix("day_")?.strip_suffix(".rs")?;
+    if digits.len() != 2 {
+        return None;
+    }
+
+    digits.parse().ok().filter(|day| (1..=25).contains(day))
+}
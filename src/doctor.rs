@@ -0,0 +1,287 @@
+use std::{env, fmt, fs, path::Path, process::Command};
+
+use crate::{input, solns, template};
+
+include!("day_file.rs");
+
+/// Something inconsistent in the repository, along with how to fix it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Problem {
+    pub subject: String,
+    pub message: String,
+    pub fix: String,
+}
+
+impl Problem {
+    fn new(subject: impl Into<String>, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            subject: subject.into(),
+            message: message.into(),
+            fix: fix.into(),
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}\n  fix: {}", self.subject, self.message, self.fix)
+    }
+}
+
+/// Cross-check solution files, the registry, stored inputs, example tests,
+/// known answers and config, printing every problem found.
+pub fn doctor() -> anyhow::Result<()> {
+    let problems = diagnose()?;
+
+    if problems.is_empty() {
+        println!("No problems found");
+        return Ok(());
+    }
+
+    for problem in &problems {
+        println!("{}\n", problem);
+    }
+
+    Err(anyhow::anyhow!("Found {} problem(s)", problems.len()))
+}
+
+pub fn diagnose() -> anyhow::Result<Vec<Problem>> {
+    let mut problems = vec![];
+
+    let mut files = vec![];
+    for entry in fs::read_dir("./src/solns")? {
+        let file_name = entry?.file_name().to_string_lossy().to_string();
+        if file_name == "mod.rs" {
+            continue;
+        }
+
+        match parse_day(&file_name) {
+            Some(day) => files.push(day),
+            None => problems.push(Problem::new(
+                format!("src/solns/{}", file_name),
+                "is not named day_XX.rs so it is never registered",
+                "rename it to day_XX.rs with a two digit day between 01 and 25",
+            )),
+        }
+    }
+    files.sort();
+
+    for day in &files {
+        let subject = format!("day {}", day);
+
+        if !solns::SOLUTIONS
+            .iter()
+            .any(|(registered, _)| registered == day)
+        {
+            problems.push(Problem::new(
+                &subject,
+                "has a solution file that is missing from this build's registry",
+                "rebuild adv so the build script picks it up",
+            ));
+        }
+
+        let source = fs::read_to_string(format!("./src/solns/day_{:02}.rs", day))?;
+        problems.extend(check_source(*day, &source));
+
+        match fs::metadata(input::path(*day)) {
+            Ok(meta) if meta.len() == 0 => problems.push(Problem::new(
+                &subject,
+                format!("has an empty input at {}", input::path(*day).display()),
+                format!("run `adv pull -d {}`", day),
+            )),
+            Ok(_) => {
+                if let Ok(known) = fs::read_to_string(input::answers_path(*day)) {
+                    problems.extend(check_answers(*day, &known, &run_output(*day)?));
+                }
+            }
+            Err(_) => problems.push(Problem::new(
+                &subject,
+                format!("has no input at {}", input::path(*day).display()),
+                format!("run `adv pull -d {}`", day),
+            )),
+        }
+    }
+
+    for (day, _) in solns::SOLUTIONS {
+        if !files.contains(day) {
+            problems.push(Problem::new(
+                format!("day {}", day),
+                "is registered but its solution file no longer exists",
+                "rebuild adv so the build script drops it",
+            ));
+        }
+    }
+
+    for day in 1..=25 {
+        if !files.contains(&day) && input::path(day).exists() {
+            problems.push(Problem::new(
+                format!("day {}", day),
+                "has an input but no solution",
+                format!("run `adv template -d {}`", day),
+            ));
+        }
+    }
+
+    problems.extend(check_config(Path::new("./.env")));
+
+    Ok(problems)
+}
+
+fn check_source(day: u8, source: &str) -> Vec<Problem> {
+    let subject = format!("day {}", day);
+    let mut problems = vec![];
+
+    let has_placeholder = template::PLACEHOLDERS
+        .iter()
+        .any(|name| source.contains(&format!("{{{{{}}}}}", name)));
+    if has_placeholder {
+        problems.push(Problem::new(
+            &subject,
+            "still contains unrendered template placeholders",
+            "replace the {{...}} placeholders by hand",
+        ));
+    }
+
+    if source.contains("EXAMPLE: &str = \"\";") {
+        problems.push(Problem::new(
+            &subject,
+            "has an empty example",
+            "paste the example from the puzzle description into EXAMPLE",
+        ));
+    }
+
+    if source.contains(".unwrap(), -1);") {
+        problems.push(Problem::new(
+            &subject,
+            "has a test that still asserts the template's -1 answer",
+            "replace -1 with the example answer from the puzzle description",
+        ));
+    }
+
+    problems
+}
+
+/// What `adv run` prints for `day`, run as a separate process since
+/// solutions print their answers rather than returning them.
+fn run_output(day: u8) -> anyhow::Result<String> {
+    let output = Command::new(env::current_exe()?)
+        .args(["run", "-d", &day.to_string()])
+        .output()?;
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The answers in the `Part N: answer` lines of `text`.
+fn answers(text: &str) -> Vec<(&str, &str)> {
+    text.lines()
+        .filter_map(|line| line.strip_prefix("Part ")?.split_once(':'))
+        .map(|(part, answer)| (part.trim(), answer.trim()))
+        .collect()
+}
+
+/// Compare the answers `adv run` gave for `day` with the `known` ones.
+fn check_answers(day: u8, known: &str, output: &str) -> Vec<Problem> {
+    let subject = format!("day {}", day);
+    let fix = format!(
+        "fix the solution, or correct {} if the known answer is wrong",
+        input::answers_path(day).display()
+    );
+    let given = answers(output);
+
+    answers(known)
+        .into_iter()
+        .filter_map(|(part, expected)| {
+            match given.iter().find(|(given_part, _)| *given_part == part) {
+                None => Some(Problem::new(
+                    &subject,
+                    format!("gave no answer for part {}", part),
+                    format!("check that `adv run -d {}` succeeds", day),
+                )),
+                Some((_, answer)) if answer != &expected => Some(Problem::new(
+                    &subject,
+                    format!(
+                        "gives {} for part {}, but the known answer is {}",
+                        answer, part, expected
+                    ),
+                    &fix,
+                )),
+                Some(_) => None,
+            }
+        })
+        .collect()
+}
+
+fn check_config(dotenv: &Path) -> Vec<Problem> {
+    if env::var("SESSION").is_ok_and(|session| !session.is_empty()) {
+        return vec![];
+    }
+
+    let message = if dotenv.exists() {
+        "SESSION is not set, so inputs cannot be pulled"
+    } else {
+        "there is no .env file, so SESSION is not set and inputs cannot be pulled"
+    };
+
+    vec![Problem::new(
+        "config",
+        message,
+        "add SESSION=<your adventofcode.com session cookie> to .env",
+    )]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check_source() {
+        let source = crate::template::BUILTIN[0].1;
+        let messages: Vec<String> = check_source(7, source)
+            .into_iter()
+            .map(|problem| problem.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec!["still contains unrendered template placeholders"]
+        );
+
+        let source = [("year", "2024"), ("day", "7"), ("title", "Day 7")]
+            .iter()
+            .fold(source.to_string(), |source, (key, value)| {
+                source.replace(&format!("{{{{{}}}}}", key), value)
+            })
+            .replace("{{example}}", "")
+            .replace("{{part1}}", "-1")
+            .replace("{{part2}}", "42");
+        assert_eq!(check_source(7, &source).len(), 2);
+
+        // Escaped braces in ordinary code are not placeholders.
+        let source = r#"fn show() -> String { format!("{{{}}}", 1) } // {{not_a_placeholder}}"#;
+        assert!(check_source(7, source).is_empty());
+    }
+
+    #[test]
+    fn test_check_answers() {
+        let known = "Part 1: 11\nPart 2: 31\n";
+        let output = "Part 1: 11\nPart 2: 30\nRan in 1.2ms\n";
+        let messages: Vec<String> = check_answers(1, known, output)
+            .into_iter()
+            .map(|problem| problem.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec!["gives 30 for part 2, but the known answer is 31"]
+        );
+
+        assert!(check_answers(1, known, "Part 1: 11\nPart 2: 31\n").is_empty());
+        assert_eq!(check_answers(1, known, "").len(), 2);
+    }
+
+    #[test]
+    fn test_parse_day() {
+        assert_eq!(parse_day("day_07.rs"), Some(7));
+        assert_eq!(parse_day("day_7.rs"), None);
+        assert_eq!(parse_day("day_26.rs"), None);
+        assert_eq!(parse_day("grid.rs"), None);
+    }
+}
@@ -0,0 +1,273 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    hash::Hash,
+};
+
+/// A directed graph stored as adjacency lists.
+///
+/// Nodes keep the order they were first added in, and every algorithm visits
+/// them in that order, so results are the same from run to run.
+#[derive(Debug, Clone)]
+pub struct Graph<N> {
+    nodes: Vec<N>,
+    index: HashMap<N, usize>,
+    edges: Vec<Vec<usize>>,
+}
+
+impl<N> Default for Graph<N> {
+    fn default() -> Self {
+        Self {
+            nodes: vec![],
+            index: HashMap::new(),
+            edges: vec![],
+        }
+    }
+}
+
+impl<N: Clone + Eq + Hash> Graph<N> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Add `node` if it is not already in the graph.
+    pub fn add_node(&mut self, node: N) {
+        self.id(node);
+    }
+
+    /// Add an edge from `from` to `to`, adding either node if needed.
+    pub fn add_edge(&mut self, from: N, to: N) {
+        let (from, to) = (self.id(from), self.id(to));
+        if !self.edges[from].contains(&to) {
+            self.edges[from].push(to);
+        }
+    }
+
+    pub fn contains(&self, node: &N) -> bool {
+        self.index.contains_key(node)
+    }
+
+    pub fn has_edge(&self, from: &N, to: &N) -> bool {
+        match (self.index.get(from), self.index.get(to)) {
+            (Some(&from), Some(to)) => self.edges[from].contains(to),
+            _ => false,
+        }
+    }
+
+    /// Every node, in the order they were added.
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    /// The nodes `node` has an edge to.
+    pub fn successors(&self, node: &N) -> impl Iterator<Item = &N> {
+        self.index
+            .get(node)
+            .into_iter()
+            .flat_map(|&id| self.edges[id].iter().map(|&to| &self.nodes[to]))
+    }
+
+    /// The graph of just `nodes` and the edges between them. Nodes not in
+    /// this graph are still added, without any edges.
+    pub fn induced(&self, nodes: impl IntoIterator<Item = N>) -> Self {
+        let mut graph = Self::new();
+        for node in nodes {
+            graph.add_node(node);
+        }
+
+        for id in 0..graph.len() {
+            let edges = self
+                .successors(&graph.nodes[id])
+                .filter_map(|to| graph.index.get(to).copied())
+                .collect();
+            graph.edges[id] = edges;
+        }
+
+        graph
+    }
+
+    /// Every node reachable from `start` by following edges, including
+    /// `start` itself if it is in the graph.
+    pub fn reachable(&self, start: &N) -> HashSet<N> {
+        let mut seen = HashSet::new();
+        let mut queue: VecDeque<usize> = self.index.get(start).copied().into_iter().collect();
+        while let Some(id) = queue.pop_front() {
+            if seen.insert(id) {
+                queue.extend(&self.edges[id]);
+            }
+        }
+
+        seen.into_iter().map(|id| self.nodes[id].clone()).collect()
+    }
+
+    /// Every node ordered so that all edges point forwards, or a cycle that
+    /// makes that impossible.
+    pub fn topological_sort(&self) -> Result<Vec<N>, Cycle<N>> {
+        let mut incoming = vec![0; self.len()];
+        for to in self.edges.iter().flatten() {
+            incoming[*to] += 1;
+        }
+
+        let mut queue: VecDeque<usize> = (0..self.len()).filter(|&id| incoming[id] == 0).collect();
+        let mut order = Vec::with_capacity(self.len());
+        while let Some(id) = queue.pop_front() {
+            order.push(self.nodes[id].clone());
+            for &to in &self.edges[id] {
+                incoming[to] -= 1;
+                if incoming[to] == 0 {
+                    queue.push_back(to);
+                }
+            }
+        }
+
+        if order.len() < self.len() {
+            return Err(self.find_cycle().expect("unsorted nodes lie on a cycle"));
+        }
+
+        Ok(order)
+    }
+
+    /// A cycle in the graph, if there is one.
+    pub fn find_cycle(&self) -> Option<Cycle<N>> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum State {
+            New,
+            OnPath,
+            Done,
+        }
+
+        let mut state = vec![State::New; self.len()];
+        for root in 0..self.len() {
+            if state[root] != State::New {
+                continue;
+            }
+
+            // Depth first, keeping the current path and how far through each
+            // node's edges it has got.
+            let mut path = vec![(root, 0)];
+            state[root] = State::OnPath;
+            while let Some((id, next)) = path.last_mut() {
+                let id = *id;
+                match self.edges[id].get(*next) {
+                    Some(&to) => {
+                        *next += 1;
+                        match state[to] {
+                            State::New => {
+                                state[to] = State::OnPath;
+                                path.push((to, 0));
+                            }
+                            State::OnPath => {
+                                let start = path.iter().position(|&(on, _)| on == to).unwrap();
+                                let nodes = path[start..]
+                                    .iter()
+                                    .map(|&(on, _)| self.nodes[on].clone())
+                                    .collect();
+                                return Some(Cycle(nodes));
+                            }
+                            State::Done => {}
+                        }
+                    }
+                    None => {
+                        state[id] = State::Done;
+                        path.pop();
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    fn id(&mut self, node: N) -> usize {
+        if let Some(&id) = self.index.get(&node) {
+            return id;
+        }
+
+        let id = self.nodes.len();
+        self.index.insert(node.clone(), id);
+        self.nodes.push(node);
+        self.edges.push(vec![]);
+        id
+    }
+}
+
+impl<N: Clone + Eq + Hash> FromIterator<(N, N)> for Graph<N> {
+    fn from_iter<I: IntoIterator<Item = (N, N)>>(iter: I) -> Self {
+        let mut graph = Self::new();
+        for (from, to) in iter {
+            graph.add_edge(from, to);
+        }
+
+        graph
+    }
+}
+
+/// The nodes of a cycle in order, each with an edge to the next and the last
+/// with an edge back to the first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle<N>(pub Vec<N>);
+
+impl<N: fmt::Debug> fmt::Display for Cycle<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Found a cycle: ")?;
+        for node in &self.0 {
+            write!(f, "{:?} -> ", node)?;
+        }
+        match self.0.first() {
+            Some(first) => write!(f, "{:?}", first),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<N: fmt::Debug> std::error::Error for Cycle<N> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_topological_sort() {
+        let graph: Graph<char> = [('c', 'a'), ('b', 'a'), ('c', 'b'), ('a', 'd')]
+            .into_iter()
+            .collect();
+        assert_eq!(graph.topological_sort(), Ok(vec!['c', 'b', 'a', 'd']));
+        assert_eq!(graph.find_cycle(), None);
+
+        let mut graph = graph;
+        graph.add_node('e');
+        assert_eq!(graph.topological_sort(), Ok(vec!['c', 'e', 'b', 'a', 'd']));
+    }
+
+    #[test]
+    fn test_cycle() {
+        let graph: Graph<u32> = [(1, 2), (2, 3), (3, 4), (4, 2)].into_iter().collect();
+        assert_eq!(graph.find_cycle(), Some(Cycle(vec![2, 3, 4])));
+        assert_eq!(graph.topological_sort(), Err(Cycle(vec![2, 3, 4])));
+        assert_eq!(
+            Cycle(vec![2, 3, 4]).to_string(),
+            "Found a cycle: 2 -> 3 -> 4 -> 2"
+        );
+
+        let induced = graph.induced([1, 2, 3, 5]);
+        assert_eq!(induced.topological_sort(), Ok(vec![1, 5, 2, 3]));
+        assert!(!induced.has_edge(&4, &2));
+    }
+
+    #[test]
+    fn test_reachable() {
+        let graph: Graph<u32> = [(1, 2), (2, 3), (4, 1)].into_iter().collect();
+        assert_eq!(graph.reachable(&1), HashSet::from([1, 2, 3]));
+        assert_eq!(graph.reachable(&3), HashSet::from([3]));
+        assert!(graph.reachable(&9).is_empty());
+        assert_eq!(graph.successors(&1).collect::<Vec<_>>(), vec![&2]);
+    }
+}
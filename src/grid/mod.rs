@@ -0,0 +1,408 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+use crate::geometry::{Direction, Direction8, Point};
+
+mod pattern;
+mod render;
+mod sparse;
+mod view;
+mod words;
+
+pub use pattern::{Match, Pattern};
+pub use render::{diff, Draw, Render, ToChar};
+pub use sparse::SparseGrid;
+pub use view::View;
+pub use words::{Hit, WordSearch};
+
+/// Lookups shared by [`Grid`] and [`SparseGrid`], so neighbor and direction
+/// based code works on either.
+pub trait GridLike<T> {
+    fn get(&self, pos: Point) -> Option<&T>;
+
+    /// Whether `pos` is a position on the grid, whether or not it has a cell.
+    fn contains(&self, pos: Point) -> bool;
+
+    /// The position one step from `pos` in `dir`, if it is on the grid.
+    fn step(&self, pos: Point, dir: impl Into<Direction8>) -> Option<Point> {
+        Some(pos + dir.into().delta()).filter(|&pos| self.contains(pos))
+    }
+
+    /// Positions starting at `pos` and stepping by `delta` until leaving the grid.
+    fn ray(&self, pos: Point, delta: Point) -> impl Iterator<Item = Point> + '_ {
+        std::iter::successors(Some(pos), move |&pos| Some(pos + delta))
+            .take_while(|&pos| self.contains(pos))
+    }
+
+    /// The orthogonal neighbors of `pos` that are on the grid.
+    fn neighbors4(&self, pos: Point) -> impl Iterator<Item = Point> + '_ {
+        pos.neighbors4()
+            .into_iter()
+            .filter(|&pos| self.contains(pos))
+    }
+
+    /// The orthogonal and diagonal neighbors of `pos` that are on the grid.
+    fn neighbors8(&self, pos: Point) -> impl Iterator<Item = Point> + '_ {
+        pos.neighbors8()
+            .into_iter()
+            .filter(|&pos| self.contains(pos))
+    }
+}
+
+/// A rectangular grid of cells stored row by row in a single `Vec`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    /// Build a grid from rows, which must all be the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> anyhow::Result<Self> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+
+        let mut cells = Vec::with_capacity(width * height);
+        for (y, row) in rows.into_iter().enumerate() {
+            if row.len() != width {
+                return Err(anyhow::anyhow!(
+                    "Row {} has {} cells but the grid is {} wide",
+                    y,
+                    row.len(),
+                    width
+                ));
+            }
+            cells.extend(row);
+        }
+
+        Ok(Self {
+            cells,
+            width,
+            height,
+        })
+    }
+
+    /// A grid the same shape with every cell converted by `f`.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, pos: Point) -> bool {
+        pos.to_flat_index(self.width, self.height).is_some()
+    }
+
+    pub fn get(&self, pos: Point) -> Option<&T> {
+        pos.to_flat_index(self.width, self.height)
+            .map(|idx| &self.cells[idx])
+    }
+
+    pub fn get_mut(&mut self, pos: Point) -> Option<&mut T> {
+        pos.to_flat_index(self.width, self.height)
+            .map(|idx| &mut self.cells[idx])
+    }
+
+    /// Every position in the grid, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Point::from((x, y))))
+    }
+
+    /// Every cell in the grid alongside its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn row(&self, y: usize) -> impl Iterator<Item = &T> {
+        self.ray(Point::from((0, y)), Direction::Right.delta())
+            .map(|pos| &self[pos])
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        self.ray(Point::from((x, 0)), Direction::Down.delta())
+            .map(|pos| &self[pos])
+    }
+
+    /// The cells from `pos` down and to the right.
+    pub fn diagonal(&self, pos: Point) -> impl Iterator<Item = &T> {
+        self.ray(pos, Direction8::DownRight.delta())
+            .map(|pos| &self[pos])
+    }
+
+    /// The cells from `pos` down and to the left.
+    pub fn anti_diagonal(&self, pos: Point) -> impl Iterator<Item = &T> {
+        self.ray(pos, Direction8::DownLeft.delta())
+            .map(|pos| &self[pos])
+    }
+
+    /// The position of the first cell, row by row, matching `predicate`.
+    pub fn find(&self, predicate: impl Fn(&T) -> bool) -> Option<Point> {
+        self.iter()
+            .find(|(_, cell)| predicate(cell))
+            .map(|(pos, _)| pos)
+    }
+
+    /// The positions of every cell equal to `value`.
+    pub fn find_all<'a>(&'a self, value: &'a T) -> impl Iterator<Item = Point> + 'a
+    where
+        T: PartialEq,
+    {
+        self.iter()
+            .filter(move |(_, cell)| *cell == value)
+            .map(|(pos, _)| pos)
+    }
+}
+
+impl<T: FromChar + Clone> Grid<T> {
+    /// Read each line of `text` as a row, converting every character to a cell.
+    pub fn parse(text: &str) -> Result<Self, GridError> {
+        Self::parse_with_markers(text, &[]).map(|(grid, _)| grid)
+    }
+
+    /// Like [`Grid::parse`], except the characters in `markers` are replaced
+    /// with the cell they are paired with and their positions collected.
+    pub fn parse_with_markers(
+        text: &str,
+        markers: &[(char, T)],
+    ) -> Result<(Self, Markers), GridError> {
+        let mut found = Markers::default();
+        let mut cells = vec![];
+        let mut width = None;
+
+        for (y, line) in text.lines().enumerate() {
+            let mut line_width = 0;
+            for (x, c) in line.chars().enumerate() {
+                let cell = match markers.iter().find(|(marker, _)| *marker == c) {
+                    Some((marker, cell)) => {
+                        found
+                            .0
+                            .entry(*marker)
+                            .or_default()
+                            .push(Point::from((x, y)));
+                        cell.clone()
+                    }
+                    None => T::from_char(c).ok_or(GridError::UnknownChar {
+                        line: y + 1,
+                        column: x + 1,
+                        found: c,
+                    })?,
+                };
+                cells.push(cell);
+                line_width += 1;
+            }
+
+            match width {
+                None => width = Some(line_width),
+                Some(width) if width != line_width => {
+                    return Err(GridError::Ragged {
+                        line: y + 1,
+                        expected: width,
+                        found: line_width,
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+
+        let width = width.filter(|&width| width > 0).ok_or(GridError::Empty)?;
+        let grid = Self {
+            height: cells.len() / width,
+            width,
+            cells,
+        };
+
+        Ok((grid, found))
+    }
+}
+
+/// Conversion from a character of puzzle input into a grid cell.
+pub trait FromChar: Sized {
+    /// The cell `c` represents, or `None` if it is not a valid cell.
+    fn from_char(c: char) -> Option<Self>;
+}
+
+impl FromChar for char {
+    fn from_char(c: char) -> Option<Self> {
+        Some(c)
+    }
+}
+
+/// Single digits, as in height maps.
+impl FromChar for u8 {
+    fn from_char(c: char) -> Option<Self> {
+        c.to_digit(10).map(|digit| digit as u8)
+    }
+}
+
+/// The positions of the marker characters found while parsing a grid.
+#[derive(Debug, Default)]
+pub struct Markers(HashMap<char, Vec<Point>>);
+
+impl Markers {
+    /// Every position `marker` was found at, row by row.
+    pub fn all(&self, marker: char) -> &[Point] {
+        self.0.get(&marker).map_or(&[], Vec::as_slice)
+    }
+
+    /// The first position `marker` was found at.
+    pub fn first(&self, marker: char) -> Option<Point> {
+        self.all(marker).first().copied()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum GridError {
+    Empty,
+    Ragged {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    UnknownChar {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GridError::Empty => write!(f, "Grid is empty"),
+            GridError::Ragged {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Line {} is {} wide but the grid is {} wide",
+                line, found, expected
+            ),
+            GridError::UnknownChar {
+                line,
+                column,
+                found,
+            } => write!(f, "Unknown character {:?} at {}:{}", found, line, column),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+impl<T> GridLike<T> for Grid<T> {
+    fn get(&self, pos: Point) -> Option<&T> {
+        Grid::get(self, pos)
+    }
+
+    fn contains(&self, pos: Point) -> bool {
+        Grid::contains(self, pos)
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Point) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{:?} is outside the grid", pos))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, pos: Point) -> &mut T {
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{:?} is outside the grid", pos))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    pub const EXAMPLE: &str = "abc
+def";
+
+    #[test]
+    fn test_parse() {
+        let grid = Grid::<char>::parse(EXAMPLE).unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get(Point::new(2, 1)), Some(&'f'));
+        assert_eq!(grid.get(Point::new(3, 0)), None);
+        assert_eq!(grid.get(Point::new(-1, 0)), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Grid::<char>::parse(""), Err(GridError::Empty));
+        assert_eq!(
+            Grid::<char>::parse("ab\nc"),
+            Err(GridError::Ragged {
+                line: 2,
+                expected: 2,
+                found: 1
+            })
+        );
+        assert_eq!(
+            Grid::<u8>::parse("12\n3x"),
+            Err(GridError::UnknownChar {
+                line: 2,
+                column: 2,
+                found: 'x'
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_with_markers() {
+        let (grid, markers) =
+            Grid::<u8>::parse_with_markers("1S\n3E", &[('S', 0), ('E', 9)]).unwrap();
+        assert_eq!(grid.row(0).collect::<Vec<_>>(), vec![&1, &0]);
+        assert_eq!(markers.first('S'), Some(Point::new(1, 0)));
+        assert_eq!(markers.all('E'), &[Point::new(1, 1)]);
+        assert_eq!(markers.first('X'), None);
+    }
+
+    #[test]
+    fn test_lines() {
+        let grid = Grid::<char>::parse(EXAMPLE).unwrap();
+        assert_eq!(grid.row(1).collect::<String>(), "def");
+        assert_eq!(grid.column(2).collect::<String>(), "cf");
+        assert_eq!(grid.diagonal(Point::new(0, 0)).collect::<String>(), "ae");
+        assert_eq!(
+            grid.anti_diagonal(Point::new(2, 0)).collect::<String>(),
+            "ce"
+        );
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid::<char>::parse(EXAMPLE).unwrap();
+        assert_eq!(
+            grid.neighbors4(Point::new(0, 0)).collect::<Vec<_>>(),
+            vec![Point::new(1, 0), Point::new(0, 1)]
+        );
+        assert_eq!(grid.neighbors8(Point::new(1, 0)).count(), 5);
+        assert_eq!(
+            grid.step(Point::new(1, 0), Direction::Down),
+            Some(Point::new(1, 1))
+        );
+        assert_eq!(grid.step(Point::new(1, 0), Direction8::UpLeft), None);
+        assert_eq!(grid.find(|c| *c == 'e'), Some(Point::new(1, 1)));
+        assert_eq!(grid.find_all(&'z').count(), 0);
+    }
+}
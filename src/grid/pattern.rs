@@ -0,0 +1,166 @@
+use crate::geometry::Point;
+
+use super::{FromChar, Grid, GridError};
+
+/// A small template to find within a larger grid, where `None` cells match
+/// anything.
+///
+/// A pattern can be allowed to match rotated or reflected, in which case each
+/// distinct orientation is kept as a variant and tried in turn.
+#[derive(Debug, Clone)]
+pub struct Pattern<T> {
+    variants: Vec<Grid<Option<T>>>,
+}
+
+/// Where a pattern was found: the position in the searched grid of the top
+/// left corner, and which of the pattern's variants matched there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    pub pos: Point,
+    pub variant: usize,
+}
+
+impl<T: Clone + PartialEq> Pattern<T> {
+    /// A pattern that only matches as `template` is.
+    pub fn new(template: Grid<Option<T>>) -> Self {
+        Self {
+            variants: vec![template],
+        }
+    }
+
+    /// Also match every quarter turn of the pattern.
+    pub fn with_rotations(self) -> Self {
+        self.with(|template| {
+            let view = template.view();
+            [view.rotate_90(), view.rotate_180(), view.rotate_270()].map(|v| v.to_grid())
+        })
+    }
+
+    /// Also match the pattern mirrored, along with whatever rotations are
+    /// already allowed.
+    pub fn with_reflections(self) -> Self {
+        self.with(|template| [template.view().flip_horizontal().to_grid()])
+    }
+
+    fn with<const N: usize>(
+        mut self,
+        transform: impl Fn(&Grid<Option<T>>) -> [Grid<Option<T>>; N],
+    ) -> Self {
+        for variant in self.variants.clone().iter().flat_map(transform) {
+            if !self.variants.contains(&variant) {
+                self.variants.push(variant);
+            }
+        }
+
+        self
+    }
+
+    /// Each distinct orientation the pattern matches in, starting with the
+    /// template as given.
+    pub fn variants(&self) -> &[Grid<Option<T>>] {
+        &self.variants
+    }
+
+    /// Whether the given variant of the pattern matches with its top left
+    /// corner at `pos`.
+    pub fn matches_at(&self, grid: &Grid<T>, pos: Point, variant: usize) -> bool {
+        self.variants[variant].iter().all(|(offset, cell)| {
+            cell.as_ref().map_or(grid.contains(pos + offset), |cell| {
+                grid.get(pos + offset) == Some(cell)
+            })
+        })
+    }
+
+    /// Every match in `grid`, row by row, with each variant that matches at
+    /// the same position listed separately.
+    pub fn find_all(&self, grid: &Grid<T>) -> Vec<Match> {
+        grid.positions()
+            .flat_map(|pos| (0..self.variants.len()).map(move |variant| Match { pos, variant }))
+            .filter(|m| self.matches_at(grid, m.pos, m.variant))
+            .collect()
+    }
+}
+
+impl<T: FromChar + Clone + PartialEq> Pattern<T> {
+    /// Read a template like [`Grid::parse`], with `wildcard` matching any
+    /// cell.
+    pub fn parse(text: &str, wildcard: char) -> Result<Self, GridError> {
+        let chars = Grid::<char>::parse(text)?;
+        let unknown = chars
+            .iter()
+            .find(|(_, &c)| c != wildcard && T::from_char(c).is_none());
+        if let Some((pos, &found)) = unknown {
+            return Err(GridError::UnknownChar {
+                line: pos.y as usize + 1,
+                column: pos.x as usize + 1,
+                found,
+            });
+        }
+
+        Ok(Self::new(chars.map(|&c| {
+            if c == wildcard {
+                None
+            } else {
+                T::from_char(c)
+            }
+        })))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_variants() {
+        let corner = Pattern::<char>::parse("ab\nc.", '.').unwrap();
+        assert_eq!(corner.clone().with_rotations().variants().len(), 4);
+        assert_eq!(corner.clone().with_reflections().variants().len(), 2);
+        assert_eq!(
+            corner.with_rotations().with_reflections().variants().len(),
+            8
+        );
+
+        let symmetric = Pattern::<char>::parse(".a.\naaa\n.a.", '.').unwrap();
+        assert_eq!(
+            symmetric
+                .with_rotations()
+                .with_reflections()
+                .variants()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_find_all() {
+        let grid = Grid::<char>::parse("abx\ncab\nxca").unwrap();
+        let pattern = Pattern::<char>::parse("ab\nc.", '.').unwrap();
+        assert_eq!(
+            pattern.find_all(&grid),
+            vec![
+                Match {
+                    pos: Point::new(0, 0),
+                    variant: 0
+                },
+                Match {
+                    pos: Point::new(1, 1),
+                    variant: 0
+                },
+            ]
+        );
+
+        // Wildcards still have to be on the grid.
+        let grid = Grid::<char>::parse("abx\ncab").unwrap();
+        assert_eq!(pattern.find_all(&grid).len(), 1);
+
+        assert_eq!(
+            Pattern::<u8>::parse("1.\n.x", '.').unwrap_err(),
+            GridError::UnknownChar {
+                line: 2,
+                column: 2,
+                found: 'x'
+            }
+        );
+    }
+}
@@ -0,0 +1,363 @@
+use std::{collections::HashMap, fmt};
+
+use text_diff::Difference;
+
+use crate::geometry::{Bounds, Point};
+
+use super::{Grid, GridLike, SparseGrid, View};
+
+/// Conversion from a grid cell back into the character it is drawn as.
+pub trait ToChar {
+    fn to_char(&self) -> char;
+}
+
+impl ToChar for char {
+    fn to_char(&self) -> char {
+        *self
+    }
+}
+
+/// Digits, with anything above 9 drawn as a letter.
+impl ToChar for u8 {
+    fn to_char(&self) -> char {
+        char::from_digit(*self as u32, 36).unwrap_or('?')
+    }
+}
+
+/// Configurable drawing of grids as text, with overlays drawn on top of the
+/// cells for things like paths walked and positions of interest.
+pub struct Render<'a, T> {
+    cell: Box<dyn Fn(&T) -> char + 'a>,
+    overlays: HashMap<Point, char>,
+    empty: char,
+}
+
+impl<'a, T> Render<'a, T> {
+    /// Draw each cell as whatever `cell` maps it to.
+    pub fn new(cell: impl Fn(&T) -> char + 'a) -> Self {
+        Self {
+            cell: Box::new(cell),
+            overlays: HashMap::new(),
+            empty: '.',
+        }
+    }
+
+    /// Draw `c` over every position in `points`, on top of earlier overlays.
+    pub fn overlay(mut self, points: impl IntoIterator<Item = Point>, c: char) -> Self {
+        self.overlays.extend(points.into_iter().map(|pos| (pos, c)));
+        self
+    }
+
+    /// Draw `c` over `pos`, on top of earlier overlays.
+    pub fn marker(self, pos: Point, c: char) -> Self {
+        self.overlay([pos], c)
+    }
+
+    /// Draw `c` wherever a sparse grid has no cell.
+    pub fn empty(self, c: char) -> Self {
+        Self { empty: c, ..self }
+    }
+
+    pub fn grid(&self, grid: &Grid<T>) -> String {
+        if grid.width() == 0 || grid.height() == 0 {
+            return String::new();
+        }
+
+        let bounds = Bounds {
+            min: Point::ORIGIN,
+            max: Point::from((grid.width() - 1, grid.height() - 1)),
+        };
+        self.draw(bounds, |pos| grid.get(pos))
+    }
+
+    pub fn view(&self, view: &View<T>) -> String {
+        if view.width() == 0 || view.height() == 0 {
+            return String::new();
+        }
+
+        let bounds = Bounds {
+            min: Point::ORIGIN,
+            max: Point::from((view.width() - 1, view.height() - 1)),
+        };
+        self.draw(bounds, |pos| view.get(pos))
+    }
+
+    /// Draw the bounds of `grid`, widened to include any overlays.
+    pub fn sparse(&self, grid: &SparseGrid<T>) -> String {
+        self.sparse_within(grid, grid.bounds())
+    }
+
+    /// Draw `grid` over `bounds`, widened to include any overlays.
+    fn sparse_within(&self, grid: &SparseGrid<T>, bounds: Option<Bounds>) -> String {
+        let bounds = self.overlays.keys().fold(bounds, |bounds, &pos| {
+            Some(bounds.map_or(Bounds::at(pos), |bounds| bounds.including(pos)))
+        });
+
+        match bounds {
+            Some(bounds) => self.draw(bounds, |pos| grid.get(pos)),
+            None => String::new(),
+        }
+    }
+
+    fn draw<'g>(&self, bounds: Bounds, get: impl Fn(Point) -> Option<&'g T>) -> String
+    where
+        T: 'g,
+    {
+        (bounds.min.y..=bounds.max.y)
+            .map(|y| {
+                (bounds.min.x..=bounds.max.x)
+                    .map(|x| {
+                        let pos = Point::new(x, y);
+                        match self.overlays.get(&pos) {
+                            Some(c) => *c,
+                            None => get(pos).map_or(self.empty, |cell| (self.cell)(cell)),
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl<'a, T: ToChar + 'a> Default for Render<'a, T> {
+    fn default() -> Self {
+        Self::new(T::to_char)
+    }
+}
+
+/// Grids that a [`Render`] can draw.
+pub trait Draw<T>: GridLike<T> {
+    fn draw(&self, render: &Render<T>) -> String;
+
+    /// Draw this grid and `other` so that the same position is in the same
+    /// place in both.
+    fn draw_aligned(&self, other: &Self, render: &Render<T>) -> (String, String) {
+        (self.draw(render), other.draw(render))
+    }
+}
+
+impl<T> Draw<T> for Grid<T> {
+    fn draw(&self, render: &Render<T>) -> String {
+        render.grid(self)
+    }
+}
+
+impl<T> Draw<T> for SparseGrid<T> {
+    fn draw(&self, render: &Render<T>) -> String {
+        render.sparse(self)
+    }
+
+    /// Both grids are drawn over the bounds holding the two of them.
+    fn draw_aligned(&self, other: &Self, render: &Render<T>) -> (String, String) {
+        let bounds = match (self.bounds(), other.bounds()) {
+            (Some(a), Some(b)) => Some(a.including(b.min).including(b.max)),
+            (a, b) => a.or(b),
+        };
+        (
+            render.sparse_within(self, bounds),
+            render.sparse_within(other, bounds),
+        )
+    }
+}
+
+impl<T> Draw<T> for View<'_, T> {
+    fn draw(&self, render: &Render<T>) -> String {
+        render.view(self)
+    }
+}
+
+impl<T: ToChar> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Render::default().grid(self))
+    }
+}
+
+impl<T: ToChar> fmt::Display for SparseGrid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Render::default().sparse(self))
+    }
+}
+
+impl<T: ToChar> fmt::Display for View<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Render::default().view(self))
+    }
+}
+
+/// A line by line diff of two grids drawn as text, with the changed cells of
+/// each changed line underlined, or `None` if they are equal.
+///
+/// Cells that differ but are drawn as the same character can't be pointed
+/// out, so grids that only differ in those are shown whole.
+pub fn diff<T, G>(left: &G, right: &G) -> Option<String>
+where
+    T: ToChar,
+    G: Draw<T> + PartialEq,
+{
+    if left == right {
+        return None;
+    }
+
+    let render = Render::default();
+    let (left, right) = left.draw_aligned(right, &render);
+    if left == right {
+        let lines = left.lines().map(|line| format!("  {}", line));
+        return Some(
+            std::iter::once("(the grids differ in cells that are drawn the same)".to_string())
+                .chain(lines)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+    }
+
+    let (_, changeset) = text_diff::diff(&left, &right, "\n");
+
+    let mut out = vec![];
+    let mut removed: Vec<String> = vec![];
+    for change in changeset {
+        match change {
+            Difference::Same(lines) => {
+                out.extend(removed.drain(..).map(|line| format!("- {}", line)));
+                out.extend(lines.lines().map(|line| format!("  {}", line)));
+            }
+            Difference::Rem(lines) => {
+                removed.extend(lines.lines().map(String::from));
+            }
+            Difference::Add(lines) => {
+                let added: Vec<&str> = lines.lines().collect();
+                // Lines replaced one for one get their changed cells marked.
+                if added.len() == removed.len() {
+                    for (old, new) in removed.drain(..).zip(added) {
+                        out.push(format!("- {}", old));
+                        out.push(format!("+ {}", new));
+                        out.push(format!("  {}", underline(&old, new)));
+                    }
+                } else {
+                    out.extend(removed.drain(..).map(|line| format!("- {}", line)));
+                    out.extend(added.iter().map(|line| format!("+ {}", line)));
+                }
+            }
+        }
+    }
+    out.extend(removed.drain(..).map(|line| format!("- {}", line)));
+
+    Some(out.join("\n"))
+}
+
+fn underline(old: &str, new: &str) -> String {
+    let (mut old, mut new) = (old.chars(), new.chars());
+    let mut marks = String::new();
+    loop {
+        match (old.next(), new.next()) {
+            (None, None) => break,
+            (a, b) if a == b => marks.push(' '),
+            _ => marks.push('^'),
+        }
+    }
+
+    marks.trim_end().to_string()
+}
+
+/// Assert that two grids are equal, showing a [`diff`](crate::grid::diff)
+/// of them if not.
+#[macro_export]
+macro_rules! assert_grid_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if left != right {
+                    let diff = $crate::grid::diff(left, right).unwrap_or_default();
+                    panic!("grids are not equal:\n{}", diff);
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let grid = Grid::<u8>::parse("123\n456").unwrap();
+        assert_eq!(grid.to_string(), "123\n456");
+
+        let render = Render::new(|n: &u8| if n.is_multiple_of(2) { '#' } else { '.' })
+            .overlay([Point::new(0, 0), Point::new(1, 0)], 'X')
+            .marker(Point::new(1, 0), '^');
+        assert_eq!(render.grid(&grid), "X^.\n#.#");
+    }
+
+    #[test]
+    fn test_render_sparse() {
+        let sparse: SparseGrid<char> = [(Point::new(-1, 0), 'a'), (Point::new(1, 1), 'b')]
+            .into_iter()
+            .collect();
+        assert_eq!(sparse.to_string(), "a..\n..b");
+
+        let render = Render::default().empty(' ').marker(Point::new(2, 0), '@');
+        assert_eq!(render.sparse(&sparse), "a  @\n  b ");
+    }
+
+    #[test]
+    fn test_diff() {
+        let left = Grid::<char>::parse("abc\ndef\nghi").unwrap();
+        let right = Grid::<char>::parse("abc\ndxf\nghi").unwrap();
+        assert_eq!(diff(&left, &left), None);
+        assert_eq!(
+            diff(&left, &right).unwrap(),
+            "  abc\n- def\n+ dxf\n   ^\n  ghi"
+        );
+    }
+
+    #[test]
+    fn test_diff_drawn_the_same() {
+        // Both of these are drawn as '?', but they are still different.
+        let left = Grid::from_rows(vec![vec![36u8]]).unwrap();
+        let right = Grid::from_rows(vec![vec![37u8]]).unwrap();
+        assert_eq!(
+            diff(&left, &right).unwrap(),
+            "(the grids differ in cells that are drawn the same)\n  ?"
+        );
+    }
+
+    #[test]
+    fn test_diff_views() {
+        let grid = Grid::<char>::parse("ab\ncd").unwrap();
+        let flipped = grid.view().flip_horizontal();
+        assert_eq!(diff(&grid.view(), &grid.view()), None);
+        assert_eq!(
+            diff(&grid.view(), &flipped).unwrap(),
+            "- ab\n+ ba\n  ^^\n- cd\n+ dc\n  ^^"
+        );
+
+        let sparse = SparseGrid::from_grid(&grid, |&c| c != 'a');
+        assert_eq!(
+            diff(&sparse, &SparseGrid::from_grid(&grid, |_| true)).unwrap(),
+            "- .b\n+ ab\n  ^\n  cd"
+        );
+    }
+
+    #[test]
+    fn test_diff_sparse_bounds() {
+        let left: SparseGrid<char> = [(Point::new(0, 0), 'a'), (Point::new(1, 0), 'b')]
+            .into_iter()
+            .collect();
+        let mut right = left.clone();
+        right.insert(Point::new(-1, 1), 'c');
+        assert_eq!(diff(&left, &right).unwrap(), "  .ab\n- ...\n+ c..\n  ^");
+
+        let empty = SparseGrid::default();
+        assert_eq!(diff(&empty, &left).unwrap(), "- ..\n+ ab\n  ^^");
+    }
+
+    #[test]
+    #[should_panic(expected = "grids are not equal")]
+    fn test_assert_grid_eq() {
+        let left = Grid::<char>::parse("ab").unwrap();
+        let right = Grid::<char>::parse("ba").unwrap();
+        assert_grid_eq!(left, right);
+    }
+}
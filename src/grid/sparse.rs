@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+
+use crate::geometry::{Bounds, Point};
+
+use super::{FromChar, Grid, GridError, GridLike};
+
+/// An unbounded grid that only stores the cells that have been set.
+///
+/// Every position is on the grid, including negative ones, so neighbors are
+/// never filtered out and rays never end on their own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point, T>,
+    bounds: Option<Bounds>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self {
+            cells: HashMap::new(),
+            bounds: None,
+        }
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// The smallest rectangle holding every cell, or `None` if there are none.
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    pub fn get(&self, pos: Point) -> Option<&T> {
+        self.cells.get(&pos)
+    }
+
+    pub fn get_mut(&mut self, pos: Point) -> Option<&mut T> {
+        self.cells.get_mut(&pos)
+    }
+
+    /// Set the cell at `pos`, returning what was there before.
+    pub fn insert(&mut self, pos: Point, cell: T) -> Option<T> {
+        self.bounds = Some(match self.bounds {
+            Some(bounds) => bounds.including(pos),
+            None => Bounds::at(pos),
+        });
+        self.cells.insert(pos, cell)
+    }
+
+    /// Clear the cell at `pos`, returning what was there.
+    pub fn remove(&mut self, pos: Point) -> Option<T> {
+        let removed = self.cells.remove(&pos)?;
+
+        // Only a cell on the edge can have been holding the bounds out.
+        let on_edge = self.bounds.is_some_and(|bounds| {
+            pos.x == bounds.min.x
+                || pos.x == bounds.max.x
+                || pos.y == bounds.min.y
+                || pos.y == bounds.max.y
+        });
+        if on_edge {
+            self.bounds = self
+                .cells
+                .keys()
+                .fold(None, |bounds: Option<Bounds>, &pos| {
+                    Some(bounds.map_or(Bounds::at(pos), |bounds| bounds.including(pos)))
+                });
+        }
+
+        Some(removed)
+    }
+
+    /// Every cell alongside its position, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells.iter().map(|(pos, cell)| (*pos, cell))
+    }
+
+    /// The cells of `grid` that `keep` accepts, at the same positions.
+    pub fn from_grid(grid: &Grid<T>, keep: impl Fn(&T) -> bool) -> Self
+    where
+        T: Clone,
+    {
+        let mut sparse = Self::new();
+        for (pos, cell) in grid.iter().filter(|(_, cell)| keep(cell)) {
+            sparse.insert(pos, cell.clone());
+        }
+
+        sparse
+    }
+
+    /// A dense grid covering the bounds, with `empty` wherever there is no
+    /// cell, along with the position its top left corner came from.
+    pub fn to_grid(&self, empty: T) -> Option<(Grid<T>, Point)>
+    where
+        T: Clone,
+    {
+        let bounds = self.bounds?;
+        let rows = (bounds.min.y..=bounds.max.y)
+            .map(|y| {
+                (bounds.min.x..=bounds.max.x)
+                    .map(|x| self.get(Point::new(x, y)).unwrap_or(&empty).clone())
+                    .collect()
+            })
+            .collect();
+        let grid = Grid::from_rows(rows).expect("every row spans the bounds");
+
+        Some((grid, bounds.min))
+    }
+}
+
+impl<T: FromChar + Clone> SparseGrid<T> {
+    /// Read `text` like [`Grid::parse`], leaving out every `empty` character.
+    pub fn parse(text: &str, empty: char) -> Result<Self, GridError> {
+        let mut sparse = Self::new();
+        for (y, line) in text.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                if c == empty {
+                    continue;
+                }
+
+                let cell = T::from_char(c).ok_or(GridError::UnknownChar {
+                    line: y + 1,
+                    column: x + 1,
+                    found: c,
+                })?;
+                sparse.insert(Point::from((x, y)), cell);
+            }
+        }
+
+        Ok(sparse)
+    }
+}
+
+impl<T> GridLike<T> for SparseGrid<T> {
+    fn get(&self, pos: Point) -> Option<&T> {
+        SparseGrid::get(self, pos)
+    }
+
+    fn contains(&self, _pos: Point) -> bool {
+        true
+    }
+}
+
+impl<T> FromIterator<(Point, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Point, T)>>(iter: I) -> Self {
+        let mut sparse = Self::new();
+        for (pos, cell) in iter {
+            sparse.insert(pos, cell);
+        }
+
+        sparse
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bounds() {
+        let mut sparse: SparseGrid<char> = [(Point::new(-3, 2), 'a'), (Point::new(5, -1), 'b')]
+            .into_iter()
+            .collect();
+        sparse.insert(Point::new(0, 0), 'c');
+        assert_eq!(sparse.len(), 3);
+        assert_eq!(
+            sparse.bounds(),
+            Some(Bounds {
+                min: Point::new(-3, -1),
+                max: Point::new(5, 2)
+            })
+        );
+
+        sparse.remove(Point::new(5, -1));
+        assert_eq!(
+            sparse.bounds(),
+            Some(Bounds {
+                min: Point::new(-3, 0),
+                max: Point::new(0, 2)
+            })
+        );
+
+        sparse.remove(Point::new(-3, 2));
+        sparse.remove(Point::new(0, 0));
+        assert_eq!(sparse.bounds(), None);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let sparse: SparseGrid<char> = SparseGrid::new();
+        assert_eq!(sparse.neighbors8(Point::ORIGIN).count(), 8);
+        assert_eq!(
+            sparse.ray(Point::ORIGIN, Point::new(-1, 0)).nth(3),
+            Some(Point::new(-3, 0))
+        );
+    }
+
+    #[test]
+    fn test_grid_round_trip() {
+        let sparse = SparseGrid::<char>::parse("..#\n#..\n...", '.').unwrap();
+        assert_eq!(sparse.len(), 2);
+
+        let (grid, origin) = sparse.to_grid('.').unwrap();
+        assert_eq!(origin, Point::ORIGIN);
+        assert_eq!(grid, Grid::parse("..#\n#..").unwrap());
+        assert_eq!(SparseGrid::from_grid(&grid, |c| *c != '.'), sparse);
+        assert_eq!(sparse.to_string(), "..#\n#..");
+    }
+}
@@ -0,0 +1,328 @@
+use crate::geometry::Point;
+
+use super::{Grid, GridLike};
+
+/// A borrowed, possibly rotated, flipped or cropped, look at a [`Grid`].
+///
+/// Views never copy cells. Each position in the view maps to a position in
+/// the grid as `origin + x * x_step + y * y_step`, so transforming a view only
+/// changes those three vectors.
+#[derive(Debug)]
+pub struct View<'a, T> {
+    grid: &'a Grid<T>,
+    origin: Point,
+    x_step: Point,
+    y_step: Point,
+    width: usize,
+    height: usize,
+}
+
+// Deriving these would needlessly require `T: Clone`.
+impl<T> Clone for View<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for View<'_, T> {}
+
+impl<T> Grid<T> {
+    /// A view of the whole grid, as is.
+    pub fn view(&self) -> View<'_, T> {
+        View {
+            grid: self,
+            origin: Point::ORIGIN,
+            x_step: Point::new(1, 0),
+            y_step: Point::new(0, 1),
+            width: self.width(),
+            height: self.height(),
+        }
+    }
+}
+
+impl<'a, T> View<'a, T> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, pos: Point) -> bool {
+        pos.to_flat_index(self.width, self.height).is_some()
+    }
+
+    pub fn get(&self, pos: Point) -> Option<&'a T> {
+        if self.contains(pos) {
+            self.grid.get(self.grid_pos(pos))
+        } else {
+            None
+        }
+    }
+
+    /// The position in the underlying grid that `pos` in this view shows.
+    pub fn grid_pos(&self, pos: Point) -> Point {
+        self.origin + self.x_step * pos.x + self.y_step * pos.y
+    }
+
+    /// Rotate a quarter turn clockwise.
+    pub fn rotate_90(self) -> Self {
+        Self {
+            origin: self.grid_pos(Point::new(0, last(self.height))),
+            x_step: -self.y_step,
+            y_step: self.x_step,
+            width: self.height,
+            height: self.width,
+            ..self
+        }
+    }
+
+    pub fn rotate_180(self) -> Self {
+        Self {
+            origin: self.grid_pos(Point::new(last(self.width), last(self.height))),
+            x_step: -self.x_step,
+            y_step: -self.y_step,
+            ..self
+        }
+    }
+
+    /// Rotate a quarter turn anticlockwise.
+    pub fn rotate_270(self) -> Self {
+        Self {
+            origin: self.grid_pos(Point::new(last(self.width), 0)),
+            x_step: self.y_step,
+            y_step: -self.x_step,
+            width: self.height,
+            height: self.width,
+            ..self
+        }
+    }
+
+    /// Swap rows and columns, mirroring along the main diagonal.
+    pub fn transpose(self) -> Self {
+        Self {
+            x_step: self.y_step,
+            y_step: self.x_step,
+            width: self.height,
+            height: self.width,
+            ..self
+        }
+    }
+
+    /// Mirror left to right.
+    pub fn flip_horizontal(self) -> Self {
+        Self {
+            origin: self.grid_pos(Point::new(last(self.width), 0)),
+            x_step: -self.x_step,
+            ..self
+        }
+    }
+
+    /// Mirror top to bottom.
+    pub fn flip_vertical(self) -> Self {
+        Self {
+            origin: self.grid_pos(Point::new(0, last(self.height))),
+            y_step: -self.y_step,
+            ..self
+        }
+    }
+
+    /// The `width` by `height` window with its top left at `pos`, if it fits.
+    pub fn window(self, pos: Point, width: usize, height: usize) -> Option<Self> {
+        let (x, y) = pos.to_index()?;
+        if x + width > self.width || y + height > self.height {
+            return None;
+        }
+
+        Some(Self {
+            origin: self.grid_pos(pos),
+            width,
+            height,
+            ..self
+        })
+    }
+
+    /// Every `width` by `height` window that fits, row by row.
+    pub fn windows(self, width: usize, height: usize) -> impl Iterator<Item = (Point, Self)> {
+        let xs = (self.width + 1).saturating_sub(width);
+        let ys = (self.height + 1).saturating_sub(height);
+        (0..ys)
+            .flat_map(move |y| (0..xs).map(move |x| Point::from((x, y))))
+            .filter_map(move |pos| Some((pos, self.window(pos, width, height)?)))
+    }
+
+    /// Every cell alongside its position in the view, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &'a T)> + '_ {
+        (0..self.height)
+            .flat_map(move |y| (0..self.width).map(move |x| Point::from((x, y))))
+            .map(|pos| (pos, &self.grid[self.grid_pos(pos)]))
+    }
+
+    /// The cells from `pos` stepping by `delta` until leaving the view.
+    pub fn line(&self, pos: Point, delta: Point) -> impl Iterator<Item = &'a T> + '_ {
+        std::iter::successors(Some(pos), move |&pos| Some(pos + delta))
+            .map_while(|pos| self.get(pos))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = &'a T> + '_> + '_ {
+        (0..self.height).map(|y| self.line(Point::from((0, y)), Point::new(1, 0)))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &'a T> + '_> + '_ {
+        (0..self.width).map(|x| self.line(Point::from((x, 0)), Point::new(0, 1)))
+    }
+
+    /// Every line running down and to the right, starting from the bottom
+    /// left corner and ending at the top right.
+    pub fn diagonals(&self) -> impl Iterator<Item = impl Iterator<Item = &'a T> + '_> + '_ {
+        let left_edge = (0..self.height).rev().map(|y| Point::from((0, y)));
+        let top_edge = (1..self.width).map(|x| Point::from((x, 0)));
+        left_edge
+            .chain(top_edge)
+            .map(|pos| self.line(pos, Point::new(1, 1)))
+    }
+
+    /// Every line running down and to the left, starting from the top left
+    /// corner and ending at the bottom right.
+    pub fn anti_diagonals(&self) -> impl Iterator<Item = impl Iterator<Item = &'a T> + '_> + '_ {
+        let top_edge = (0..self.width).map(|x| Point::from((x, 0)));
+        let right_edge = (1..self.height).map(|y| Point::from((last(self.width), y as i64)));
+        top_edge
+            .chain(right_edge)
+            .map(|pos| self.line(pos, Point::new(-1, 1)))
+    }
+
+    /// Copy the cells of the view into a new grid.
+    pub fn to_grid(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let rows = self.rows().map(|row| row.cloned().collect()).collect();
+        Grid::from_rows(rows).expect("every row of a view is the same width")
+    }
+}
+
+impl<T> GridLike<T> for View<'_, T> {
+    fn get(&self, pos: Point) -> Option<&T> {
+        View::get(self, pos)
+    }
+
+    fn contains(&self, pos: Point) -> bool {
+        View::contains(self, pos)
+    }
+}
+
+/// Views are equal when they show the same cells, wherever they come from.
+impl<T: PartialEq> PartialEq for View<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.width, self.height) == (other.width, other.height)
+            && self
+                .iter()
+                .map(|(_, cell)| cell)
+                .eq(other.iter().map(|(_, cell)| cell))
+    }
+}
+
+impl<T: Eq> Eq for View<'_, T> {}
+
+/// The index of the last of `len` cells, or zero if there are none.
+fn last(len: usize) -> i64 {
+    len.saturating_sub(1) as i64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The example from day 4, which has 18 `XMAS`s in every direction.
+    pub const EXAMPLE: &str = "MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX";
+
+    fn text(view: View<char>) -> String {
+        view.rows()
+            .map(|row| row.collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn count(line: impl Iterator<Item = char>) -> usize {
+        line.collect::<String>().matches("XMAS").count()
+    }
+
+    #[test]
+    fn test_transforms() {
+        let grid = Grid::<char>::parse("abc\ndef").unwrap();
+        let view = grid.view();
+        assert_eq!(text(view.rotate_90()), "da\neb\nfc");
+        assert_eq!(text(view.rotate_180()), "fed\ncba");
+        assert_eq!(text(view.rotate_270()), "cf\nbe\nad");
+        assert_eq!(text(view.transpose()), "ad\nbe\ncf");
+        assert_eq!(text(view.flip_horizontal()), "cba\nfed");
+        assert_eq!(text(view.flip_vertical()), "def\nabc");
+        assert_eq!(text(view.rotate_90().rotate_90()), text(view.rotate_180()));
+        assert_eq!(
+            text(view.rotate_90().flip_horizontal()),
+            text(view.transpose())
+        );
+        assert_eq!(
+            view.rotate_270().to_grid().view().rotate_90().to_grid(),
+            grid
+        );
+    }
+
+    #[test]
+    fn test_windows() {
+        let grid = Grid::<char>::parse("abc\ndef").unwrap();
+        let window = grid.view().window(Point::new(1, 0), 2, 2).unwrap();
+        assert_eq!(text(window), "bc\nef");
+        assert_eq!(text(window.rotate_180()), "fe\ncb");
+        assert!(grid.view().window(Point::new(2, 0), 2, 1).is_none());
+        assert_eq!(grid.view().windows(2, 2).count(), 2);
+    }
+
+    #[test]
+    fn test_lines() {
+        let grid = Grid::<char>::parse("abc\ndef").unwrap();
+        let view = grid.view();
+        let lines = |lines: Vec<String>| lines.join(" ");
+        assert_eq!(
+            lines(view.columns().map(|c| c.collect()).collect()),
+            "ad be cf"
+        );
+        assert_eq!(
+            lines(view.diagonals().map(|d| d.collect()).collect()),
+            "d ae bf c"
+        );
+        assert_eq!(
+            lines(view.anti_diagonals().map(|d| d.collect()).collect()),
+            "a bd ce f"
+        );
+    }
+
+    #[test]
+    fn test_day_04() {
+        let grid = Grid::<char>::parse(EXAMPLE).unwrap();
+        let view = grid.view();
+        let rotations = [view, view.rotate_90(), view.rotate_180(), view.rotate_270()];
+
+        let total: usize = rotations
+            .iter()
+            .map(|view| {
+                let rows: usize = view.rows().map(|row| count(row.copied())).sum();
+                let diagonals: usize = view.diagonals().map(|d| count(d.copied())).sum();
+                rows + diagonals
+            })
+            .sum();
+
+        assert_eq!(total, 18);
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::geometry::{Direction8, Point};
+
+use super::{Grid, GridLike};
+
+/// A set of words to find in a grid of letters, reading in any of the eight
+/// directions.
+///
+/// The words are compiled into an Aho-Corasick automaton, so the grid is read
+/// once per direction however many words there are.
+#[derive(Debug, Clone)]
+pub struct WordSearch {
+    words: Vec<String>,
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    next: HashMap<char, usize>,
+    /// The node for the longest proper suffix that is also a prefix of a word.
+    fail: usize,
+    /// The words ending here, including those ending at any suffix.
+    words: Vec<usize>,
+}
+
+/// A word found in a grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hit<'a> {
+    /// The position of the first letter.
+    pub start: Point,
+    /// The direction the word reads in from there.
+    pub dir: Direction8,
+    pub word: &'a str,
+}
+
+impl WordSearch {
+    /// Search for each of `words`, ignoring any repeats of the same word.
+    pub fn new<S: AsRef<str>>(words: impl IntoIterator<Item = S>) -> Self {
+        let mut seen = HashSet::new();
+        let words: Vec<String> = words
+            .into_iter()
+            .map(|word| word.as_ref().to_string())
+            .filter(|word| seen.insert(word.clone()))
+            .collect();
+
+        // Build a trie of every word.
+        let mut nodes = vec![Node::default()];
+        for (idx, word) in words.iter().enumerate().filter(|(_, w)| !w.is_empty()) {
+            let mut node = 0;
+            for c in word.chars() {
+                node = match nodes[node].next.get(&c) {
+                    Some(&next) => next,
+                    None => {
+                        nodes.push(Node::default());
+                        let next = nodes.len() - 1;
+                        nodes[node].next.insert(c, next);
+                        next
+                    }
+                };
+            }
+            nodes[node].words.push(idx);
+        }
+
+        // Then link each node to its longest suffix in the trie, breadth
+        // first so that shorter suffixes are always linked already.
+        let mut queue: VecDeque<usize> = nodes[0].next.values().copied().collect();
+        while let Some(node) = queue.pop_front() {
+            let edges: Vec<(char, usize)> =
+                nodes[node].next.iter().map(|(&c, &n)| (c, n)).collect();
+            for (c, child) in edges {
+                let mut fail = nodes[node].fail;
+                while fail != 0 && !nodes[fail].next.contains_key(&c) {
+                    fail = nodes[fail].fail;
+                }
+                let fail = nodes[fail].next.get(&c).copied().unwrap_or(0);
+
+                nodes[child].fail = fail;
+                let inherited = nodes[fail].words.clone();
+                nodes[child].words.extend(inherited);
+                queue.push_back(child);
+            }
+        }
+
+        Self { words, nodes }
+    }
+
+    fn step(&self, mut node: usize, c: char) -> usize {
+        loop {
+            if let Some(&next) = self.nodes[node].next.get(&c) {
+                return next;
+            }
+            if node == 0 {
+                return 0;
+            }
+            node = self.nodes[node].fail;
+        }
+    }
+
+    /// Every occurrence of every word in `grid`, ordered by where they start
+    /// and then by direction.
+    ///
+    /// Words that read the same both ways, or have a single letter, are found
+    /// once for each direction they read in.
+    pub fn find_all(&self, grid: &Grid<char>) -> Vec<Hit<'_>> {
+        let mut hits = vec![];
+        for dir in Direction8::ALL {
+            let delta = dir.delta();
+
+            // Each line in this direction starts from a cell with nothing
+            // before it.
+            let starts = grid.positions().filter(|&pos| !grid.contains(pos - delta));
+            for start in starts {
+                let mut node = 0;
+                for (len, pos) in grid.ray(start, delta).enumerate() {
+                    node = self.step(node, grid[pos]);
+                    for &word in &self.nodes[node].words {
+                        let word = &self.words[word];
+                        let back = word.chars().count() as i64 - 1;
+                        debug_assert!(back <= len as i64);
+                        hits.push(Hit {
+                            start: pos - delta * back,
+                            dir,
+                            word,
+                        });
+                    }
+                }
+            }
+        }
+
+        hits.sort_by_key(|hit| (hit.start.y, hit.start.x, hit.dir));
+        hits
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find_all() {
+        let grid = Grid::<char>::parse("cat\naaa\ntac").unwrap();
+        let search = WordSearch::new(["cat", "at"]);
+        let hits = search.find_all(&grid);
+
+        let found = |word: &str| -> Vec<(Point, Direction8)> {
+            hits.iter()
+                .filter(|hit| hit.word == word)
+                .map(|hit| (hit.start, hit.dir))
+                .collect()
+        };
+        assert_eq!(
+            found("cat"),
+            vec![
+                (Point::new(0, 0), Direction8::Right),
+                (Point::new(0, 0), Direction8::Down),
+                (Point::new(2, 2), Direction8::Up),
+                (Point::new(2, 2), Direction8::Left),
+            ]
+        );
+        assert_eq!(found("at").len(), 6);
+    }
+
+    #[test]
+    fn test_overlapping_words() {
+        let grid = Grid::<char>::parse("abcd").unwrap();
+        let search = WordSearch::new(["abcd", "bc", "c", "bcd", ""]);
+        let words: Vec<&str> = search
+            .find_all(&grid)
+            .into_iter()
+            .filter(|hit| hit.dir == Direction8::Right)
+            .map(|hit| hit.word)
+            .collect();
+        assert_eq!(words, vec!["abcd", "bc", "bcd", "c"]);
+    }
+
+    #[test]
+    fn test_duplicate_words() {
+        let grid = Grid::<char>::parse("ab").unwrap();
+        let search = WordSearch::new(["ab", "ab"]);
+        assert_eq!(search.find_all(&grid).len(), 1);
+    }
+}
@@ -0,0 +1,198 @@
+use std::{fs, path::PathBuf};
+
+/// Where the puzzle input for `day` is stored.
+pub fn path(day: u8) -> PathBuf {
+    PathBuf::from(format!("./puzzles/day_{:02}.txt", day))
+}
+
+/// Where the accepted answers for `day` are kept, as the `Part 1: ...` and
+/// `Part 2: ...` lines that `adv run` prints.
+pub fn answers_path(day: u8) -> PathBuf {
+    PathBuf::from(format!("./puzzles/day_{:02}.answers", day))
+}
+
+/// Read the stored puzzle input for `day`, [normalised](normalise).
+pub fn load(day: u8) -> anyhow::Result<(String, Changes)> {
+    let path = path(day);
+    let raw = fs::read_to_string(&path).map_err(|e| {
+        anyhow::anyhow!(
+            "Could not read {} ({}), run `adv pull -d {}` first",
+            path.display(),
+            e,
+            day
+        )
+    })?;
+
+    Ok(normalise(&raw))
+}
+
+/// What [`normalise`] changed about an input.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Changes {
+    pub removed_bom: bool,
+    /// Lines that ended in `\r\n`.
+    pub crlf_lines: usize,
+    /// Lines that had whitespace after their last character.
+    pub trimmed_lines: usize,
+    /// Blank lines dropped from the end.
+    pub trailing_blank_lines: usize,
+    /// Whether the last line had no newline after it.
+    pub added_final_newline: bool,
+    /// The line and column, from 1, of every non-ASCII character. These are
+    /// kept, but usually mean the input was mangled by copy and paste.
+    pub non_ascii: Vec<(usize, usize, char)>,
+}
+
+impl Changes {
+    /// A line describing each kind of change, with non-ASCII characters only
+    /// mentioned if `warn_non_ascii` is set.
+    pub fn report(&self, warn_non_ascii: bool) -> Vec<String> {
+        let mut report = vec![];
+        if self.removed_bom {
+            report.push("Removed a byte order mark".to_string());
+        }
+        if self.crlf_lines > 0 {
+            report.push(format!("Converted {} CRLF line ending(s)", self.crlf_lines));
+        }
+        if self.trimmed_lines > 0 {
+            report.push(format!(
+                "Trimmed trailing whitespace from {} line(s)",
+                self.trimmed_lines
+            ));
+        }
+        if self.trailing_blank_lines > 0 {
+            report.push(format!(
+                "Dropped {} blank line(s) from the end",
+                self.trailing_blank_lines
+            ));
+        }
+        if self.added_final_newline {
+            report.push("Added a newline after the last line".to_string());
+        }
+        if warn_non_ascii {
+            if let Some((line, column, c)) = self.non_ascii.first() {
+                report.push(format!(
+                    "Found {} non-ASCII character(s), the first is {:?} at {}:{}",
+                    self.non_ascii.len(),
+                    c,
+                    line,
+                    column
+                ));
+            }
+        }
+
+        report
+    }
+}
+
+/// Clean up `raw` so that solutions can assume plain `\n` separated lines
+/// with nothing trailing, ending in a single newline.
+pub fn normalise(raw: &str) -> (String, Changes) {
+    let mut changes = Changes::default();
+
+    let text = match raw.strip_prefix('\u{feff}') {
+        Some(text) => {
+            changes.removed_bom = true;
+            text
+        }
+        None => raw,
+    };
+
+    let mut lines = vec![];
+    for (idx, line) in text.split('\n').enumerate() {
+        if line.ends_with('\r') {
+            changes.crlf_lines += 1;
+        }
+        let trimmed = line.trim_end();
+        if trimmed.len() < line.trim_end_matches('\r').len() {
+            changes.trimmed_lines += 1;
+        }
+
+        changes.non_ascii.extend(
+            trimmed
+                .chars()
+                .enumerate()
+                .filter(|(_, c)| !c.is_ascii())
+                .map(|(column, c)| (idx + 1, column + 1, c)),
+        );
+        lines.push(trimmed);
+    }
+
+    // Splitting leaves one empty line after the final newline, which is kept.
+    while lines.len() > 1 && lines[lines.len() - 2].is_empty() {
+        lines.pop();
+        changes.trailing_blank_lines += 1;
+    }
+
+    let mut text = lines.join("\n");
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+        changes.added_final_newline = true;
+    }
+
+    (text, changes)
+}
+
+/// Store `puzzle` as the input for `day`.
+pub fn save(day: u8, puzzle: &[u8]) -> anyhow::Result<()> {
+    let path = path(day);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, puzzle)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_normalise() {
+        let (text, changes) = normalise("\u{feff}ab \r\ncd\r\n\r\n  \n");
+        assert_eq!(text, "ab\ncd\n");
+        assert_eq!(
+            changes,
+            Changes {
+                removed_bom: true,
+                crlf_lines: 3,
+                trimmed_lines: 2,
+                trailing_blank_lines: 2,
+                added_final_newline: false,
+                non_ascii: vec![],
+            }
+        );
+        assert_eq!(changes.report(true).len(), 4);
+    }
+
+    #[test]
+    fn test_normalise_clean() {
+        let (text, changes) = normalise("  a\n\nb\n");
+        assert_eq!(text, "  a\n\nb\n");
+        assert_eq!(changes, Changes::default());
+
+        let (text, changes) = normalise("caf\u{e9}\n");
+        assert_eq!(text, "caf\u{e9}\n");
+        assert_eq!(changes.non_ascii, vec![(1, 4, '\u{e9}')]);
+        assert!(changes.report(false).is_empty());
+        assert_eq!(
+            changes.report(true),
+            vec!["Found 1 non-ASCII character(s), the first is 'é' at 1:4"]
+        );
+    }
+
+    #[test]
+    fn test_normalise_final_newline() {
+        let (text, changes) = normalise("a\nb");
+        assert_eq!(text, "a\nb\n");
+        assert!(changes.added_final_newline);
+        assert_eq!(
+            changes.report(false),
+            vec!["Added a newline after the last line"]
+        );
+
+        assert!(!normalise("a\nb\n").1.added_final_newline);
+        assert_eq!(normalise(""), (String::new(), Changes::default()));
+    }
+}
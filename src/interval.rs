@@ -0,0 +1,305 @@
+use std::ops::Range;
+
+/// A set of integers stored as sorted, disjoint, half-open ranges.
+///
+/// Ranges that overlap or touch are merged as they are added, so the set has
+/// exactly one representation and comparing sets compares their members.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet {
+    ranges: Vec<Range<i64>>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The ranges making up the set, in order.
+    pub fn ranges(&self) -> &[Range<i64>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// How many integers are in the set.
+    pub fn total_len(&self) -> u64 {
+        self.ranges
+            .iter()
+            .map(|range| range.start.abs_diff(range.end))
+            .sum()
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        let idx = self.ranges.partition_point(|r| r.end <= value);
+        self.ranges.get(idx).is_some_and(|r| r.start <= value)
+    }
+
+    /// Whether every integer in `range` is in the set.
+    pub fn contains_range(&self, range: Range<i64>) -> bool {
+        if range.is_empty() {
+            return true;
+        }
+
+        let idx = self.ranges.partition_point(|r| r.end <= range.start);
+        self.ranges
+            .get(idx)
+            .is_some_and(|r| r.start <= range.start && range.end <= r.end)
+    }
+
+    /// Add every integer in `range`.
+    pub fn insert(&mut self, range: Range<i64>) {
+        if range.is_empty() {
+            return;
+        }
+
+        // Everything from `lo` to `hi` overlaps or touches the new range.
+        let lo = self.ranges.partition_point(|r| r.end < range.start);
+        let hi = self.ranges.partition_point(|r| r.start <= range.end);
+        let merged = if lo < hi {
+            range.start.min(self.ranges[lo].start)..range.end.max(self.ranges[hi - 1].end)
+        } else {
+            range
+        };
+        self.ranges.splice(lo..hi, [merged]);
+    }
+
+    /// Take out every integer in `range`.
+    pub fn remove(&mut self, range: Range<i64>) {
+        if range.is_empty() {
+            return;
+        }
+
+        // Everything from `lo` to `hi` overlaps the removed range, and only
+        // the ends of the first and last can be left over.
+        let lo = self.ranges.partition_point(|r| r.end <= range.start);
+        let hi = self.ranges.partition_point(|r| r.start < range.end);
+        if lo >= hi {
+            return;
+        }
+
+        let before = self.ranges[lo].start..range.start;
+        let after = range.end..self.ranges[hi - 1].end;
+        let kept = [before, after].into_iter().filter(|r| !r.is_empty());
+        self.ranges.splice(lo..hi, kept);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut ranges: Vec<Range<i64>> = vec![];
+        let (mut a, mut b) = (
+            self.ranges.iter().peekable(),
+            other.ranges.iter().peekable(),
+        );
+
+        // Take whichever range starts first, merging it into the last one if
+        // they overlap or touch.
+        loop {
+            let next = match (a.peek(), b.peek()) {
+                (Some(left), Some(right)) if right.start < left.start => b.next(),
+                (Some(_), _) => a.next(),
+                (None, _) => b.next(),
+            };
+            let Some(next) = next else { break };
+
+            match ranges.last_mut() {
+                Some(last) if next.start <= last.end => last.end = last.end.max(next.end),
+                _ => ranges.push(next.clone()),
+            }
+        }
+
+        Self { ranges }
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let (mut a, mut b) = (0, 0);
+        while let (Some(left), Some(right)) = (self.ranges.get(a), other.ranges.get(b)) {
+            let overlap = left.start.max(right.start)..left.end.min(right.end);
+            if !overlap.is_empty() {
+                ranges.push(overlap);
+            }
+
+            // Whichever ends first cannot overlap anything further on.
+            if left.end < right.end {
+                a += 1;
+            } else {
+                b += 1;
+            }
+        }
+
+        Self { ranges }
+    }
+
+    /// The integers in this set but not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let mut cuts = other.ranges.iter().peekable();
+        for range in &self.ranges {
+            let mut start = range.start;
+            while let Some(cut) = cuts.peek() {
+                if cut.end <= start {
+                    cuts.next();
+                    continue;
+                }
+                if cut.start >= range.end {
+                    break;
+                }
+
+                if cut.start > start {
+                    ranges.push(start..cut.start);
+                }
+                start = cut.end;
+
+                // A cut running past this range may cut the next one too.
+                if cut.end > range.end {
+                    break;
+                }
+                cuts.next();
+            }
+
+            if start < range.end {
+                ranges.push(start..range.end);
+            }
+        }
+
+        Self { ranges }
+    }
+
+    /// The set with every member moved by `offset`, or `None` if that would
+    /// take any of them outside of an `i64`.
+    pub fn shifted(&self, offset: i64) -> Option<Self> {
+        let ranges = self
+            .ranges
+            .iter()
+            .map(|r| Some(r.start.checked_add(offset)?..r.end.checked_add(offset)?))
+            .collect::<Option<_>>()?;
+
+        Some(Self { ranges })
+    }
+
+    /// The members within `range` moved by `offset`, and the members
+    /// outside of it left where they are, as a pair.
+    ///
+    /// Mapping a set through a list of offset ranges, like seeds through an
+    /// almanac, applies this for each range to what is still unmapped.
+    /// Returns `None` if the moved members would overflow.
+    pub fn split_shifted(&self, range: Range<i64>, offset: i64) -> Option<(Self, Self)> {
+        let within = self.intersection(&Self::from(range.clone()));
+        let mut outside = self.clone();
+        outside.remove(range);
+
+        Some((within.shifted(offset)?, outside))
+    }
+}
+
+impl From<Range<i64>> for IntervalSet {
+    fn from(range: Range<i64>) -> Self {
+        let mut set = Self::new();
+        set.insert(range);
+        set
+    }
+}
+
+impl FromIterator<Range<i64>> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Range<i64>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for range in iter {
+            set.insert(range);
+        }
+
+        set
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_insert_remove() {
+        let mut set: IntervalSet = [5..8, 0..2, 2..3, 10..12].into_iter().collect();
+        assert_eq!(set.ranges(), &[0..3, 5..8, 10..12]);
+        assert_eq!(set.total_len(), 8);
+
+        set.insert(7..10);
+        assert_eq!(set.ranges(), &[0..3, 5..12]);
+
+        set.remove(1..6);
+        assert_eq!(set.ranges(), &[0..1, 6..12]);
+        assert!(set.contains(0) && !set.contains(1) && set.contains(11));
+        assert!(set.contains_range(7..12) && !set.contains_range(0..7));
+    }
+
+    #[test]
+    fn test_split_shifted() {
+        let seeds: IntervalSet = [79..93, 55..68].into_iter().collect();
+        let (moved, unmoved) = seeds.split_shifted(50..98, 2).unwrap();
+        assert_eq!(moved.ranges(), &[57..70, 81..95]);
+        assert!(unmoved.is_empty());
+
+        let (moved, unmoved) = seeds.split_shifted(60..80, -10).unwrap();
+        assert_eq!(moved.ranges(), &[50..58, 69..70]);
+        assert_eq!(unmoved.ranges(), &[55..60, 80..93]);
+
+        assert_eq!(seeds.shifted(i64::MAX - 92), None);
+        assert_eq!(seeds.split_shifted(90..100, i64::MAX), None);
+        assert!(seeds.split_shifted(0..10, i64::MAX).is_some());
+    }
+
+    fn ranges() -> impl Strategy<Value = Vec<Range<i64>>> {
+        prop::collection::vec((-50i64..50, 0i64..20).prop_map(|(a, len)| a..a + len), 0..8)
+    }
+
+    fn members(ranges: &[Range<i64>]) -> BTreeSet<i64> {
+        ranges.iter().cloned().flatten().collect()
+    }
+
+    fn is_canonical(set: &IntervalSet) -> bool {
+        set.ranges().iter().all(|r| !r.is_empty())
+            && set
+                .ranges()
+                .windows(2)
+                .all(|pair| pair[0].end < pair[1].start)
+    }
+
+    proptest! {
+        #[test]
+        fn prop_members(a in ranges(), value in -60i64..80) {
+            let set: IntervalSet = a.iter().cloned().collect();
+            let expected = members(&a);
+            prop_assert!(is_canonical(&set));
+            prop_assert_eq!(members(set.ranges()), expected.clone());
+            prop_assert_eq!(set.total_len(), expected.len() as u64);
+            prop_assert_eq!(set.contains(value), expected.contains(&value));
+        }
+
+        #[test]
+        fn prop_set_operations(a in ranges(), b in ranges()) {
+            let (left, right): (IntervalSet, IntervalSet) =
+                (a.iter().cloned().collect(), b.iter().cloned().collect());
+            let (a, b) = (members(&a), members(&b));
+
+            let union = left.union(&right);
+            let intersection = left.intersection(&right);
+            let difference = left.difference(&right);
+            for set in [&union, &intersection, &difference] {
+                prop_assert!(is_canonical(set));
+            }
+            prop_assert_eq!(members(union.ranges()), &a | &b);
+            prop_assert_eq!(members(intersection.ranges()), &a & &b);
+            prop_assert_eq!(members(difference.ranges()), &a - &b);
+        }
+
+        #[test]
+        fn prop_contains_range(a in ranges(), start in -60i64..80, len in 0i64..10) {
+            let set: IntervalSet = a.iter().cloned().collect();
+            let expected = (start..start + len).all(|n| set.contains(n));
+            prop_assert_eq!(set.contains_range(start..start + len), expected);
+        }
+    }
+}
@@ -0,0 +1,24 @@
+//! Advent of Code solutions and the tooling around them.
+//!
+//! The `adv` binary is a thin CLI over this library: [`solns`] holds the
+//! solution registry, [`input`] loads and stores puzzle inputs, [`pull`]
+//! fetches them from adventofcode.com, [`template`] scaffolds new days and
+//! [`doctor`] checks that they are all consistent.
+
+// Lets code generated by `#[derive(AocParse)]` name `::adv` from inside the crate.
+extern crate self as adv;
+
+pub mod cycle;
+pub mod doctor;
+pub mod geometry;
+pub mod graph;
+pub mod grid;
+pub mod input;
+pub mod interval;
+pub mod math;
+pub mod parse;
+pub mod pathfinding;
+pub mod pull;
+pub mod run;
+pub mod solns;
+pub mod template;
@@ -1,28 +1,53 @@
-mod pull;
-mod run;
-mod solns;
-
+use adv::{doctor, input, pull, run, template};
 use clap::{Parser, Subcommand};
-use std::{fs::File, io::Write};
+use std::path::PathBuf;
 
 fn main() -> Result<(), anyhow::Error> {
-    dotenvy::dotenv()?;
+    // A missing .env is reported by `adv doctor` and only matters when pulling.
+    if let Err(e) = dotenvy::dotenv() {
+        if !e.not_found() {
+            return Err(e.into());
+        }
+    }
 
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Run { day } => run::run(day)?,
+        Commands::Run {
+            day,
+            warn_non_ascii,
+        } => run::run(day, warn_non_ascii)?,
         Commands::Pull { day } => {
             println!("Pulling for day {}", day);
             let session = std::env::var("SESSION")?;
-            let puzzle = pull::pull(&session, day).unwrap();
-            let mut file = File::create(format!("./puzzles/day_{:02}.txt", day))?;
-            file.write_all(&puzzle)?;
+            let puzzle = pull::pull(&session, day)?;
+            input::save(day, &puzzle)?;
         }
-        Commands::Template { day } => {
-            let mut file = File::create(format!("./src/solns/day_{:02}.rs", day))?;
-            file.write_all(solns::TEMPLATE.as_bytes())?;
+        Commands::Template {
+            day,
+            name,
+            dir,
+            force,
+            year,
+            title,
+            example,
+            part1,
+            part2,
+        } => {
+            let ctx = template::Context {
+                day,
+                year,
+                title: title.unwrap_or_else(|| format!("Day {}", day)),
+                example: match example {
+                    Some(path) => std::fs::read_to_string(path)?,
+                    None => String::new(),
+                },
+                part1,
+                part2,
+            };
+            template::template(&name, &dir, &ctx, force)?;
         }
+        Commands::Doctor => doctor::doctor()?,
     }
 
     Ok(())
@@ -43,6 +68,9 @@ pub enum Commands {
         /// The day of the solution to run
         #[arg(short, long)]
         day: u8,
+        /// Warn about any non-ASCII characters in the input
+        #[arg(long)]
+        warn_non_ascii: bool,
     },
     /// Pull the puzzle data for the specified day
     Pull {
@@ -55,5 +83,31 @@ pub enum Commands {
         /// The day to template
         #[arg(short, long)]
         day: u8,
+        /// The template to use, either from the template directory or built in (lines, grid, sections)
+        #[arg(short, long, default_value = "lines")]
+        name: String,
+        /// The directory to look for custom templates in
+        #[arg(long, default_value = "./templates")]
+        dir: PathBuf,
+        /// Overwrite the solution if it already exists
+        #[arg(short, long)]
+        force: bool,
+        /// The year of the puzzle
+        #[arg(short, long, default_value_t = 2024)]
+        year: u16,
+        /// The title of the puzzle
+        #[arg(short, long)]
+        title: Option<String>,
+        /// A file containing the example input to test against
+        #[arg(short, long)]
+        example: Option<PathBuf>,
+        /// The expected answer to part 1 of the example
+        #[arg(long, default_value = "-1")]
+        part1: String,
+        /// The expected answer to part 2 of the example
+        #[arg(long, default_value = "-1")]
+        part2: String,
     },
+    /// Check the solutions, inputs and config for problems
+    Doctor,
 }
@@ -0,0 +1,385 @@
+//! Number theory and counting over `i64` and `i128`.
+//!
+//! Everything that can overflow is checked and fails with a [`MathError`]
+//! rather than wrapping, so solutions can use `?` on the results.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathError {
+    Overflow,
+    /// A modulus, or similar, that must be positive was not.
+    NotPositive,
+    /// A value that must not be negative was.
+    Negative,
+    /// The value has no inverse for the modulus, as they share a factor.
+    NoInverse,
+    /// The congruences contradict each other.
+    NoSolution,
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MathError::Overflow => write!(f, "Arithmetic overflow"),
+            MathError::NotPositive => write!(f, "Expected a positive modulus"),
+            MathError::Negative => write!(f, "Expected a value that is not negative"),
+            MathError::NoInverse => write!(f, "No modular inverse exists"),
+            MathError::NoSolution => write!(f, "The congruences have no common solution"),
+        }
+    }
+}
+
+impl std::error::Error for MathError {}
+
+pub type Result<T> = std::result::Result<T, MathError>;
+
+/// The signed integers the functions here work over.
+pub trait Integer: Copy + Ord + fmt::Debug {
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn checked_abs(self) -> Option<Self>;
+    /// Division rounding towards zero, or `None` for a zero divisor or
+    /// overflow.
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+    /// The remainder in `0..rhs`, for positive `rhs`.
+    fn rem_euclid(self, rhs: Self) -> Self;
+    fn is_odd(self) -> bool;
+    fn half(self) -> Self;
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {$(
+        impl Integer for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_sub(self, rhs)
+            }
+
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_mul(self, rhs)
+            }
+
+            fn checked_abs(self) -> Option<Self> {
+                <$t>::checked_abs(self)
+            }
+
+            fn checked_div(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_div(self, rhs)
+            }
+
+            fn rem_euclid(self, rhs: Self) -> Self {
+                <$t>::rem_euclid(self, rhs)
+            }
+
+            fn is_odd(self) -> bool {
+                self & 1 == 1
+            }
+
+            fn half(self) -> Self {
+                self >> 1
+            }
+        }
+    )*};
+}
+
+impl_integer!(i64, i128);
+
+fn add<T: Integer>(a: T, b: T) -> Result<T> {
+    a.checked_add(b).ok_or(MathError::Overflow)
+}
+
+fn sub<T: Integer>(a: T, b: T) -> Result<T> {
+    a.checked_sub(b).ok_or(MathError::Overflow)
+}
+
+fn mul<T: Integer>(a: T, b: T) -> Result<T> {
+    a.checked_mul(b).ok_or(MathError::Overflow)
+}
+
+fn div<T: Integer>(a: T, b: T) -> Result<T> {
+    a.checked_div(b).ok_or(MathError::Overflow)
+}
+
+fn positive<T: Integer>(modulus: T) -> Result<T> {
+    if modulus > T::ZERO {
+        Ok(modulus)
+    } else {
+        Err(MathError::NotPositive)
+    }
+}
+
+/// The greatest common divisor, which is never negative.
+pub fn gcd<T: Integer>(a: T, b: T) -> Result<T> {
+    let (mut a, mut b) = (a, b);
+    while b != T::ZERO {
+        (a, b) = (b, a.rem_euclid(b.checked_abs().ok_or(MathError::Overflow)?));
+    }
+
+    a.checked_abs().ok_or(MathError::Overflow)
+}
+
+/// The least common multiple, which is never negative.
+pub fn lcm<T: Integer>(a: T, b: T) -> Result<T> {
+    if a == T::ZERO || b == T::ZERO {
+        return Ok(T::ZERO);
+    }
+
+    let a = a.checked_abs().ok_or(MathError::Overflow)?;
+    let b = b.checked_abs().ok_or(MathError::Overflow)?;
+    mul(div(a, gcd(a, b)?)?, b)
+}
+
+/// The least common multiple of every value, or one if there are none.
+pub fn lcm_all<T: Integer>(values: impl IntoIterator<Item = T>) -> Result<T> {
+    values.into_iter().try_fold(T::ONE, lcm)
+}
+
+/// `(g, x, y)` such that `a * x + b * y == g`, where `g` is the greatest
+/// common divisor of `a` and `b`.
+pub fn extended_gcd<T: Integer>(a: T, b: T) -> Result<(T, T, T)> {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (T::ONE, T::ZERO);
+    let (mut old_y, mut y) = (T::ZERO, T::ONE);
+    while r != T::ZERO {
+        let q = div(old_r, r)?;
+        (old_r, r) = (r, sub(old_r, mul(q, r)?)?);
+        (old_x, x) = (x, sub(old_x, mul(q, x)?)?);
+        (old_y, y) = (y, sub(old_y, mul(q, y)?)?);
+    }
+
+    if old_r < T::ZERO {
+        let negate = |n: T| sub(T::ZERO, n);
+        return Ok((negate(old_r)?, negate(old_x)?, negate(old_y)?));
+    }
+
+    Ok((old_r, old_x, old_y))
+}
+
+/// `a * b` modulo `modulus`, without overflowing even if the product would.
+pub fn mod_mul<T: Integer>(a: T, b: T, modulus: T) -> Result<T> {
+    let modulus = positive(modulus)?;
+    let (mut a, mut b) = (a.rem_euclid(modulus), b.rem_euclid(modulus));
+    if let Some(product) = a.checked_mul(b) {
+        return Ok(product.rem_euclid(modulus));
+    }
+
+    // Double and add, keeping every intermediate below the modulus.
+    let add_mod = |a: T, b: T| match modulus.checked_sub(b) {
+        Some(gap) if a >= gap => a.checked_sub(gap).unwrap(),
+        _ => a.checked_add(b).unwrap(),
+    };
+    let mut product = T::ZERO;
+    while b > T::ZERO {
+        if b.is_odd() {
+            product = add_mod(product, a);
+        }
+        a = add_mod(a, a);
+        b = b.half();
+    }
+
+    Ok(product)
+}
+
+/// `base` to the power of `exp` modulo `modulus`.
+pub fn mod_pow<T: Integer>(base: T, exp: T, modulus: T) -> Result<T> {
+    let modulus = positive(modulus)?;
+    if exp < T::ZERO {
+        return mod_pow(mod_inverse(base, modulus)?, sub(T::ZERO, exp)?, modulus);
+    }
+
+    let (mut base, mut exp) = (base.rem_euclid(modulus), exp);
+    let mut result = T::ONE.rem_euclid(modulus);
+    while exp > T::ZERO {
+        if exp.is_odd() {
+            result = mod_mul(result, base, modulus)?;
+        }
+        base = mod_mul(base, base, modulus)?;
+        exp = exp.half();
+    }
+
+    Ok(result)
+}
+
+/// The `x` in `0..modulus` with `a * x` equal to one modulo `modulus`.
+pub fn mod_inverse<T: Integer>(a: T, modulus: T) -> Result<T> {
+    let modulus = positive(modulus)?;
+    let (g, x, _) = extended_gcd(a.rem_euclid(modulus), modulus)?;
+    if g != T::ONE {
+        return Err(MathError::NoInverse);
+    }
+
+    Ok(x.rem_euclid(modulus))
+}
+
+/// The smallest `x` that is `residue` modulo `modulus` for every pair, along
+/// with the modulus all solutions repeat at.
+///
+/// The moduli need not be coprime, as long as the congruences agree.
+pub fn crt<T: Integer>(congruences: impl IntoIterator<Item = (T, T)>) -> Result<(T, T)> {
+    congruences
+        .into_iter()
+        .try_fold((T::ZERO, T::ONE), |(x, m), (residue, modulus)| {
+            let modulus = positive(modulus)?;
+            let (g, p, _) = extended_gcd(m, modulus)?;
+            let diff = sub(residue, x)?;
+            if diff.rem_euclid(g) != T::ZERO {
+                return Err(MathError::NoSolution);
+            }
+
+            // Step x along by multiples of m until it also meets the new
+            // congruence, which takes `diff / g * p` steps modulo `modulus / g`.
+            let lcm = mul(div(m, g)?, modulus)?;
+            let steps = mod_mul(div(diff, g)?, p, div(modulus, g)?)?;
+            let x = add(x, mul(m, steps)?)?.rem_euclid(lcm);
+            Ok((x, lcm))
+        })
+}
+
+/// The largest `r` with `r * r <= n`.
+pub fn isqrt<T: Integer>(n: T) -> Result<T> {
+    if n < T::ZERO {
+        return Err(MathError::Negative);
+    }
+    if n < add(T::ONE, T::ONE)? {
+        return Ok(n);
+    }
+
+    // Newton's method from above, which only ever decreases.
+    let mut r = add(n.half(), T::ONE)?;
+    loop {
+        let next = add(r, div(n, r)?)?.half();
+        if next >= r {
+            return Ok(r);
+        }
+        r = next;
+    }
+}
+
+/// `n!`.
+pub fn factorial<T: Integer>(n: T) -> Result<T> {
+    permutations(n, n)
+}
+
+/// How many ordered ways there are to pick `k` of `n` things.
+pub fn permutations<T: Integer>(n: T, k: T) -> Result<T> {
+    if n < T::ZERO || k < T::ZERO {
+        return Err(MathError::Negative);
+    }
+    if k > n {
+        return Ok(T::ZERO);
+    }
+
+    let mut result = T::ONE;
+    let mut factor = n;
+    while factor > sub(n, k)? {
+        result = mul(result, factor)?;
+        factor = sub(factor, T::ONE)?;
+    }
+
+    Ok(result)
+}
+
+/// How many ways there are to choose `k` of `n` things, ignoring order.
+pub fn binomial<T: Integer>(n: T, k: T) -> Result<T> {
+    if n < T::ZERO || k < T::ZERO {
+        return Err(MathError::Negative);
+    }
+    if k > n {
+        return Ok(T::ZERO);
+    }
+
+    // Each partial product is itself a binomial coefficient, so the division
+    // is always exact.
+    let k = k.min(sub(n, k)?);
+    let mut result = T::ONE;
+    let mut i = T::ZERO;
+    while i < k {
+        let numerator = sub(n, i)?;
+        i = add(i, T::ONE)?;
+        let g = gcd(result, i)?;
+        result = mul(div(result, g)?, div(numerator, div(i, g)?)?)?;
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(gcd(12i64, -18), Ok(6));
+        assert_eq!(gcd(0i64, 0), Ok(0));
+        assert_eq!(gcd(i64::MIN, 0), Err(MathError::Overflow));
+        assert_eq!(lcm(4i64, 6), Ok(12));
+        assert_eq!(lcm_all([2i64, 3, 4, 5]), Ok(60));
+        assert_eq!(lcm(i64::MAX, i64::MAX - 1), Err(MathError::Overflow));
+        assert!(lcm(i64::MAX as i128, i64::MAX as i128 - 1).is_ok());
+
+        let (g, x, y) = extended_gcd(240i64, 46).unwrap();
+        assert_eq!(g, 2);
+        assert_eq!(240 * x + 46 * y, 2);
+        assert_eq!(extended_gcd(i64::MIN, -1), Err(MathError::Overflow));
+    }
+
+    #[test]
+    fn test_modular() {
+        assert_eq!(mod_pow(4i64, 13, 497), Ok(445));
+        assert_eq!(mod_pow(3i64, -1, 7), Ok(5));
+        assert_eq!(mod_pow(2i64, 10, 1), Ok(0));
+        assert_eq!(mod_inverse(3i64, 11), Ok(4));
+        assert_eq!(mod_inverse(-3i64, 11), Ok(7));
+        assert_eq!(mod_inverse(6i64, 9), Err(MathError::NoInverse));
+        assert_eq!(mod_inverse(3i64, 0), Err(MathError::NotPositive));
+
+        // Large enough that the product overflows and has to be doubled up.
+        let m = i64::MAX - 24;
+        assert_eq!(
+            mod_mul(m - 1, m - 2, m),
+            Ok(((m as i128 - 1) * (m as i128 - 2) % m as i128) as i64)
+        );
+        assert_eq!(mod_pow(2i128, 127, i128::MAX), Ok(1));
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt([(2i64, 3), (3, 5), (2, 7)]), Ok((23, 105)));
+        assert_eq!(crt([(1i64, 4), (3, 6)]), Ok((9, 12)));
+        assert_eq!(crt([(1i64, 4), (2, 6)]), Err(MathError::NoSolution));
+        assert_eq!(crt(Vec::<(i64, i64)>::new()), Ok((0, 1)));
+    }
+
+    #[test]
+    fn test_isqrt() {
+        assert_eq!(isqrt(0i64), Ok(0));
+        assert_eq!(isqrt(15i64), Ok(3));
+        assert_eq!(isqrt(16i64), Ok(4));
+        assert_eq!(isqrt(i64::MAX), Ok(3_037_000_499));
+        assert_eq!(isqrt(-1i64), Err(MathError::Negative));
+    }
+
+    #[test]
+    fn test_counting() {
+        assert_eq!(factorial(0i64), Ok(1));
+        assert_eq!(factorial(20i64), Ok(2_432_902_008_176_640_000));
+        assert_eq!(factorial(21i64), Err(MathError::Overflow));
+        assert!(factorial(21i128).is_ok());
+        assert_eq!(permutations(5i64, 2), Ok(20));
+        assert_eq!(binomial(5i64, 2), Ok(10));
+        assert_eq!(binomial(5i64, 7), Ok(0));
+        assert_eq!(binomial(62i64, 31), Ok(465_428_353_255_261_088));
+        assert_eq!(binomial(-1i64, 0), Err(MathError::Negative));
+    }
+}
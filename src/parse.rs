@@ -0,0 +1,473 @@
+use std::{any::Any, fmt, str::FromStr};
+
+use crate::grid::{FromChar, Grid, GridError, Markers};
+
+pub use adv_derive::AocParse;
+
+#[doc(hidden)]
+pub mod derive;
+
+/// A problem with puzzle input, located by line and column from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    /// How many characters from `column` are at fault, or zero if it is the
+    /// line as a whole.
+    pub len: usize,
+    /// The line of input the problem is on, if known.
+    pub snippet: String,
+    pub message: String,
+}
+
+impl ParseError {
+    /// A problem with a whole line.
+    pub fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            column: 1,
+            len: 0,
+            snippet: String::new(),
+            message: message.into(),
+        }
+    }
+
+    /// A problem with `span`, which must be sliced from `text`.
+    pub fn at(text: &str, span: &str, message: impl Into<String>) -> Self {
+        let (line, column, snippet) = locate(text, span);
+        Self {
+            line,
+            column,
+            len: span.chars().count().max(1),
+            snippet: snippet.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// The same error for input that starts `offset` lines further down.
+    fn shifted(self, offset: usize) -> Self {
+        Self {
+            line: self.line + offset,
+            ..self
+        }
+    }
+
+    /// The same error, found while parsing `part`, located within the `text`
+    /// that `part` was sliced from.
+    pub fn within(self, text: &str, part: &str) -> Self {
+        let (line, column, snippet) = locate(text, part);
+        if self.line > 1 {
+            return self.shifted(line - 1);
+        }
+
+        Self {
+            line,
+            column: column + self.column - 1,
+            snippet: snippet.to_string(),
+            ..self
+        }
+    }
+}
+
+/// The line and column that `span` starts at within `text`, and the whole of
+/// that line.
+fn locate<'a>(text: &'a str, span: &str) -> (usize, usize, &'a str) {
+    let offset = (span.as_ptr() as usize)
+        .checked_sub(text.as_ptr() as usize)
+        .filter(|&offset| offset <= text.len())
+        .expect("span is sliced from text");
+
+    let start = text[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+    let end = text[offset..]
+        .find('\n')
+        .map_or(text.len(), |idx| offset + idx);
+    let line = text[..offset].matches('\n').count() + 1;
+    let column = text[start..offset].chars().count() + 1;
+
+    (line, column, text[start..end].trim_end_matches('\r'))
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.len == 0 {
+            write!(f, "Line {}: {}", self.line, self.message)?;
+        } else {
+            write!(
+                f,
+                "Line {}, column {}: {}",
+                self.line, self.column, self.message
+            )?;
+        }
+
+        if !self.snippet.is_empty() {
+            write!(f, "\n  {}", self.snippet)?;
+            if self.len > 0 {
+                let indent = " ".repeat(self.column - 1);
+                write!(f, "\n  {}{}", indent, "^".repeat(self.len))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Grid errors carry no snippet, see [`grid`] for one that does.
+impl From<GridError> for ParseError {
+    fn from(e: GridError) -> Self {
+        let (line, column, len) = match e {
+            GridError::Empty => (1, 1, 0),
+            GridError::Ragged {
+                line,
+                expected,
+                found,
+            } => (line, expected.min(found) + 1, expected.abs_diff(found)),
+            GridError::UnknownChar { line, column, .. } => (line, column, 1),
+        };
+
+        Self {
+            line,
+            column,
+            len,
+            snippet: String::new(),
+            message: e.to_string(),
+        }
+    }
+}
+
+/// A block of lines separated from its neighbors by blank lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Section<'a> {
+    /// The line number of the first line of the section.
+    pub line: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Section<'a> {
+    /// Parse each line of the section, with errors numbered from the start of
+    /// the whole input.
+    pub fn lines<T>(
+        &self,
+        parse: impl Fn(&'a str) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        lines(self.text, parse).map_err(|e| e.shifted(self.line - 1))
+    }
+}
+
+/// Parse each line of `text`, numbering any error by the line it came from.
+pub fn lines<'a, T>(
+    text: &'a str,
+    parse: impl Fn(&'a str) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    text.lines()
+        .map(|line| parse(line).map_err(|e| e.within(text, line)))
+        .collect()
+}
+
+/// Split `text` into the sections between blank lines.
+pub fn sections(text: &str) -> Vec<Section<'_>> {
+    let mut sections = vec![];
+    let mut start: Option<(usize, usize)> = None;
+    let mut offset = 0;
+
+    for (idx, line) in text.split_inclusive('\n').enumerate() {
+        let is_blank = line.trim().is_empty();
+        match (start, is_blank) {
+            (None, false) => start = Some((idx + 1, offset)),
+            (Some((line, from)), true) => {
+                sections.push(Section {
+                    line,
+                    text: text[from..offset].trim_end(),
+                });
+                start = None;
+            }
+            _ => {}
+        }
+        offset += line.len();
+    }
+
+    if let Some((line, from)) = start {
+        sections.push(Section {
+            line,
+            text: text[from..].trim_end(),
+        });
+    }
+
+    sections
+}
+
+/// Every integer in `line`, in order.
+///
+/// A `-` directly before a number makes it negative, unless it follows
+/// another digit, so both `x=-3` and ranges like `1-3` read as expected.
+/// Numbers too big for an `i64` are an error rather than being skipped, so
+/// that the rest keep their places.
+pub fn ints(line: &str) -> Result<Vec<i64>, ParseError> {
+    let bytes = line.as_bytes();
+    let mut ints = vec![];
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        if !bytes[idx].is_ascii_digit() {
+            idx += 1;
+            continue;
+        }
+
+        let is_negative =
+            idx > 0 && bytes[idx - 1] == b'-' && (idx < 2 || !bytes[idx - 2].is_ascii_digit());
+        let start = if is_negative { idx - 1 } else { idx };
+        while idx < bytes.len() && bytes[idx].is_ascii_digit() {
+            idx += 1;
+        }
+
+        let span = &line[start..idx];
+        ints.push(
+            span.parse()
+                .map_err(|e| failed(e, line, span, || format!("Could not parse {:?}", span)))?,
+        );
+    }
+
+    Ok(ints)
+}
+
+/// Parse a single value, failing with the offending text.
+pub fn value<T>(text: &str) -> Result<T, ParseError>
+where
+    T: FromStr,
+    T::Err: fmt::Display + 'static,
+{
+    let trimmed = text.trim();
+    trimmed.parse().map_err(|e| {
+        failed(e, text, trimmed, || {
+            format!("Could not parse {:?}", trimmed)
+        })
+    })
+}
+
+/// The error for `span` of `text` failing to parse.
+///
+/// A [`ParseError`] from a nested parser is moved to where `span` is, and
+/// any other error is described after `context`.
+fn failed<E>(e: E, text: &str, span: &str, context: impl FnOnce() -> String) -> ParseError
+where
+    E: fmt::Display + 'static,
+{
+    match (&e as &dyn Any).downcast_ref::<ParseError>() {
+        Some(nested) => nested.clone().within(text, span),
+        None => ParseError::at(text, span, format!("{}: {}", context(), e)),
+    }
+}
+
+/// Parse `line` as a list of values separated by `sep`.
+pub fn list<T>(line: &str, sep: &str) -> Result<Vec<T>, ParseError>
+where
+    T: FromStr,
+    T::Err: fmt::Display + 'static,
+{
+    line.split(sep)
+        .map(|part| value(part).map_err(|e| e.within(line, part)))
+        .collect()
+}
+
+/// Parse `line` as a list of values separated by whitespace.
+pub fn words<T>(line: &str) -> Result<Vec<T>, ParseError>
+where
+    T: FromStr,
+    T::Err: fmt::Display + 'static,
+{
+    line.split_whitespace()
+        .map(|word| value(word).map_err(|e| e.within(line, word)))
+        .collect()
+}
+
+/// Parse `line` as two values either side of `sep`, like `47|53`.
+pub fn pair<A, B>(line: &str, sep: &str) -> Result<(A, B), ParseError>
+where
+    A: FromStr,
+    A::Err: fmt::Display + 'static,
+    B: FromStr,
+    B::Err: fmt::Display + 'static,
+{
+    let (a, b) = line
+        .split_once(sep)
+        .ok_or_else(|| ParseError::at(line, line, format!("Expected {:?}", sep)))?;
+
+    Ok((
+        value(a).map_err(|e| e.within(line, a))?,
+        value(b).map_err(|e| e.within(line, b))?,
+    ))
+}
+
+/// Parse `text` as a grid of cells.
+pub fn grid<T: FromChar + Clone>(text: &str) -> Result<Grid<T>, ParseError> {
+    Grid::parse(text).map_err(|e| grid_error(e, text))
+}
+
+/// Parse `text` as a grid of cells, like [`Grid::parse_with_markers`].
+pub fn grid_with_markers<T: FromChar + Clone>(
+    text: &str,
+    markers: &[(char, T)],
+) -> Result<(Grid<T>, Markers), ParseError> {
+    Grid::parse_with_markers(text, markers).map_err(|e| grid_error(e, text))
+}
+
+fn grid_error(e: GridError, text: &str) -> ParseError {
+    let e = ParseError::from(e);
+    match text.lines().nth(e.line - 1) {
+        Some(line) => ParseError {
+            snippet: line.to_string(),
+            ..e
+        },
+        None => e,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ints() {
+        assert_eq!(ints("p=0,4 v=3,-3"), Ok(vec![0, 4, 3, -3]));
+        assert_eq!(ints("1-3 a: abcde"), Ok(vec![1, 3]));
+        assert_eq!(ints("-12 and --5"), Ok(vec![-12, -5]));
+        assert_eq!(ints("none"), Ok(vec![]));
+        assert_eq!(
+            error(ints("1 99999999999999999999 3")),
+            "Line 1, column 3: Could not parse \"99999999999999999999\": number too large to fit in target type
+  1 99999999999999999999 3
+    ^^^^^^^^^^^^^^^^^^^^"
+        );
+    }
+
+    #[test]
+    fn test_sections() {
+        let text = "a\nb\n\n\nc\n  \nd\n";
+        let sections = sections(text);
+        assert_eq!(
+            sections,
+            vec![
+                Section {
+                    line: 1,
+                    text: "a\nb"
+                },
+                Section { line: 5, text: "c" },
+                Section { line: 7, text: "d" },
+            ]
+        );
+    }
+
+    fn error<T: fmt::Debug>(result: Result<T, ParseError>) -> String {
+        result.unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_lines() {
+        let text = "1,2\n3,x\n";
+        assert_eq!(
+            error(lines(text, |line| list::<i32>(line, ","))),
+            "Line 2, column 3: Could not parse \"x\": invalid digit found in string
+  3,x
+    ^"
+        );
+
+        let sections = sections("1|2\n\n3|4\n5-6");
+        assert_eq!(
+            error(sections[1].lines(|line| pair::<i32, i32>(line, "|"))),
+            "Line 4, column 1: Expected \"|\"\n  5-6\n  ^^^"
+        );
+    }
+
+    #[test]
+    fn test_values() {
+        assert_eq!(words::<i32>("7 6  4"), Ok(vec![7, 6, 4]));
+        assert_eq!(pair::<i32, i32>("3   4", "   "), Ok((3, 4)));
+        assert_eq!(
+            error(words::<i32>("7 6  -x4")),
+            "Line 1, column 6: Could not parse \"-x4\": invalid digit found in string
+  7 6  -x4
+       ^^^"
+        );
+        assert_eq!(
+            error(grid::<u8>("12\n3")),
+            "Line 2, column 2: Line 2 is 1 wide but the grid is 2 wide\n  3\n   ^"
+        );
+        assert_eq!(
+            error(lines("ok\nbad", |_| Err::<(), _>(ParseError::new(
+                1, "Nope"
+            )))),
+            "Line 1: Nope\n  ok"
+        );
+    }
+
+    #[derive(Debug, PartialEq, AocParse)]
+    #[aoc(pattern = "{name} at {pos} carrying {items}")]
+    struct Elf {
+        name: String,
+        pos: Coord,
+        #[aoc(sep = ",")]
+        items: Vec<u32>,
+    }
+
+    #[derive(Debug, PartialEq, AocParse)]
+    #[aoc(pattern = "<{x}, {y}>")]
+    struct Coord {
+        x: i64,
+        y: i64,
+    }
+
+    #[derive(Debug, PartialEq, AocParse)]
+    enum Instruction {
+        #[aoc(pattern = "move {dx} {dy}")]
+        Move { dx: i32, dy: i32 },
+        #[aoc(pattern = "stop")]
+        Stop,
+    }
+
+    #[test]
+    fn test_derive() {
+        assert_eq!(
+            "Buddy at <3, -4> carrying 1,2,3".parse(),
+            Ok(Elf {
+                name: "Buddy".to_string(),
+                pos: Coord { x: 3, y: -4 },
+                items: vec![1, 2, 3],
+            })
+        );
+        assert_eq!(
+            lines("move 1 -2\nstop", str::parse),
+            Ok(vec![Instruction::Move { dx: 1, dy: -2 }, Instruction::Stop])
+        );
+    }
+
+    #[test]
+    fn test_derive_errors() {
+        assert_eq!(
+            error("Buddy at <3, -4> carrying 1,x".parse::<Elf>()),
+            "Line 1, column 29: Could not parse `items[1]`: invalid digit found in string
+  Buddy at <3, -4> carrying 1,x
+                              ^"
+        );
+        assert_eq!(
+            error("Buddy at <3, -z> carrying 1".parse::<Elf>()),
+            "Line 1, column 14: Could not parse `y`: invalid digit found in string
+  Buddy at <3, -z> carrying 1
+               ^^"
+        );
+        assert_eq!(
+            error("<3 -4>".parse::<Coord>()),
+            "Line 1, column 2: Expected \", \" after `x`\n  <3 -4>\n   ^^^^^"
+        );
+        assert_eq!(
+            error("<3, 4>!".parse::<Coord>()),
+            "Line 1, column 7: Unexpected text after the pattern\n  <3, 4>!\n        ^"
+        );
+        assert_eq!(
+            lines("stop\nstart", str::parse::<Instruction>)
+                .unwrap_err()
+                .line,
+            2
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! Runtime support for code generated by [`AocParse`](super::AocParse).
+
+use std::{fmt, str::FromStr};
+
+use super::{failed, ParseError};
+
+/// A piece of a pattern, either text that must appear as is or a field that
+/// captures everything up to the next piece of text.
+pub enum Segment {
+    Literal(&'static str),
+    Field(&'static str),
+}
+
+/// Match `input` against `pattern`, capturing the text of each field in order.
+///
+/// A field stops at the first match of the text after it, or runs to the end
+/// of the input if it comes last.
+pub fn captures<'a>(input: &'a str, pattern: &[Segment]) -> Result<Vec<&'a str>, ParseError> {
+    let mut captures = vec![];
+    let mut offset = 0;
+    let mut field: Option<&str> = None;
+
+    for segment in pattern {
+        match (segment, field.take()) {
+            (Segment::Field(name), _) => field = Some(name),
+            (Segment::Literal(text), Some(name)) => {
+                let len = input[offset..].find(text).ok_or_else(|| {
+                    ParseError::at(
+                        input,
+                        &input[offset..],
+                        format!("Expected {:?} after `{}`", text, name),
+                    )
+                })?;
+                captures.push(&input[offset..offset + len]);
+                offset += len + text.len();
+            }
+            (Segment::Literal(text), None) => {
+                if !input[offset..].starts_with(text) {
+                    return Err(ParseError::at(
+                        input,
+                        &input[offset..],
+                        format!("Expected {:?}", text),
+                    ));
+                }
+                offset += text.len();
+            }
+        }
+    }
+
+    if field.is_some() {
+        captures.push(&input[offset..]);
+    } else if offset < input.len() {
+        return Err(ParseError::at(
+            input,
+            &input[offset..],
+            "Unexpected text after the pattern",
+        ));
+    }
+
+    Ok(captures)
+}
+
+/// Parse the value of the field `name` from `capture`, a slice of `input`.
+pub fn field<T>(input: &str, name: &str, capture: &str) -> Result<T, ParseError>
+where
+    T: FromStr,
+    T::Err: fmt::Display + 'static,
+{
+    let text = capture.trim();
+    text.parse()
+        .map_err(|e| failed(e, input, text, || format!("Could not parse `{}`", name)))
+}
+
+/// Parse the value of the field `name` as a list of values split by `sep`.
+pub fn list<T>(input: &str, name: &str, capture: &str, sep: &str) -> Result<Vec<T>, ParseError>
+where
+    T: FromStr,
+    T::Err: fmt::Display + 'static,
+{
+    capture
+        .split(sep)
+        .enumerate()
+        .map(|(idx, item)| field(input, &format!("{}[{}]", name, idx), item))
+        .collect()
+}
+
+/// The error for `input` matching none of the variants of `name`.
+pub fn no_variant(input: &str, name: &str, errors: Vec<ParseError>) -> ParseError {
+    let reasons: Vec<String> = errors.into_iter().map(|e| e.message).collect();
+    ParseError::at(
+        input,
+        input,
+        format!("Matches no variant of {}: {}", name, reasons.join("; ")),
+    )
+}
@@ -0,0 +1,348 @@
+//! Shortest paths and reachability over anything that can list the neighbors
+//! of a node.
+//!
+//! Every search takes a closure giving the neighbors of a node, so the same
+//! code works on grids, graphs and implicit state spaces alike. For grids,
+//! [`steps`] and [`weighted_steps`] build that closure from a test of which
+//! cells can be entered.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+use crate::{
+    geometry::Point,
+    grid::{Grid, GridLike},
+};
+
+/// Everything a search reached, with the cheapest cost to each node and the
+/// node it was reached from.
+#[derive(Debug, Clone)]
+pub struct Paths<N, C> {
+    start: N,
+    costs: HashMap<N, C>,
+    previous: HashMap<N, N>,
+}
+
+impl<N: Clone + Eq + Hash, C: Copy> Paths<N, C> {
+    fn new(start: N, zero: C) -> Self {
+        Self {
+            costs: HashMap::from([(start.clone(), zero)]),
+            previous: HashMap::new(),
+            start,
+        }
+    }
+
+    pub fn start(&self) -> &N {
+        &self.start
+    }
+
+    /// The cost of the cheapest path to `node`, if it was reached.
+    pub fn cost(&self, node: &N) -> Option<C> {
+        self.costs.get(node).copied()
+    }
+
+    /// The cheapest path from the start to `node`, including both ends.
+    pub fn path_to(&self, node: &N) -> Option<Vec<N>> {
+        if !self.costs.contains_key(node) {
+            return None;
+        }
+
+        let mut path = vec![node.clone()];
+        while let Some(previous) = self.previous.get(path.last().unwrap()) {
+            path.push(previous.clone());
+        }
+        path.reverse();
+
+        Some(path)
+    }
+
+    /// Every node reached, alongside its cost, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&N, C)> {
+        self.costs.iter().map(|(node, cost)| (node, *cost))
+    }
+
+    /// How many nodes were reached, including the start.
+    pub fn len(&self) -> usize {
+        self.costs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.costs.is_empty()
+    }
+}
+
+/// The fewest steps from `start` to every node it can reach.
+pub fn bfs<N, I>(start: N, mut neighbors: impl FnMut(&N) -> I) -> Paths<N, usize>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut paths = Paths::new(start.clone(), 0);
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        let cost = paths.costs[&node];
+        for next in neighbors(&node) {
+            if !paths.costs.contains_key(&next) {
+                paths.costs.insert(next.clone(), cost + 1);
+                paths.previous.insert(next.clone(), node.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+
+    paths
+}
+
+/// The cheapest cost from `start` to every node it can reach, where
+/// `neighbors` gives each neighbor with the cost of moving to it.
+pub fn dijkstra<N, C, I>(start: N, neighbors: impl FnMut(&N) -> I) -> Paths<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    search(start, neighbors, |_| C::default(), |_| false).0
+}
+
+/// The cheapest path from `start` to a node `is_goal` accepts, and its cost.
+///
+/// `heuristic` must never overestimate the remaining cost, or the path found
+/// may not be the cheapest. It need not be consistent, as nodes are explored
+/// again whenever a cheaper way to them turns up. With a heuristic of zero
+/// this is Dijkstra's algorithm stopping at the first goal.
+pub fn astar<N, C, I>(
+    start: N,
+    neighbors: impl FnMut(&N) -> I,
+    heuristic: impl Fn(&N) -> C,
+    is_goal: impl Fn(&N) -> bool,
+) -> Option<(Vec<N>, C)>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let (paths, goal) = search(start, neighbors, heuristic, is_goal);
+    let goal = goal?;
+
+    Some((paths.path_to(&goal)?, paths.cost(&goal)?))
+}
+
+/// A* search, exploring everything if no goal is found.
+fn search<N, C, I>(
+    start: N,
+    mut neighbors: impl FnMut(&N) -> I,
+    heuristic: impl Fn(&N) -> C,
+    is_goal: impl Fn(&N) -> bool,
+) -> (Paths<N, C>, Option<N>)
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut paths = Paths::new(start.clone(), C::default());
+
+    // The heap holds indices into `queued` so that nodes need not be `Ord`.
+    let mut queued = vec![(start.clone(), C::default())];
+    let mut heap = BinaryHeap::from([(Reverse(heuristic(&start)), Reverse(0))]);
+
+    while let Some((_, Reverse(idx))) = heap.pop() {
+        let (node, cost) = queued[idx].clone();
+        // A node is queued again each time a cheaper way to it is found, so
+        // skip any entries that have since been beaten.
+        if paths.cost(&node).is_some_and(|best| best < cost) {
+            continue;
+        }
+        if is_goal(&node) {
+            return (paths, Some(node));
+        }
+
+        for (next, step) in neighbors(&node) {
+            let next_cost = cost + step;
+            if paths.cost(&next).is_some_and(|known| known <= next_cost) {
+                continue;
+            }
+
+            paths.costs.insert(next.clone(), next_cost);
+            paths.previous.insert(next.clone(), node.clone());
+            heap.push((Reverse(next_cost + heuristic(&next)), Reverse(queued.len())));
+            queued.push((next, next_cost));
+        }
+    }
+
+    (paths, None)
+}
+
+/// Every node reachable from `start`, including itself.
+pub fn flood_fill<N, I>(start: N, neighbors: impl FnMut(&N) -> I) -> HashSet<N>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    bfs(start, neighbors).costs.into_keys().collect()
+}
+
+/// Split `nodes` into groups that can reach each other, in the order the
+/// first node of each group appears.
+///
+/// The neighbor relation should be symmetric, otherwise a group holds what
+/// its first node can reach.
+pub fn connected_components<N, I>(
+    nodes: impl IntoIterator<Item = N>,
+    mut neighbors: impl FnMut(&N) -> I,
+) -> Vec<HashSet<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut seen = HashSet::new();
+    let mut components = vec![];
+    for node in nodes {
+        if seen.contains(&node) {
+            continue;
+        }
+
+        let component = flood_fill(node, &mut neighbors);
+        seen.extend(component.iter().cloned());
+        components.push(component);
+    }
+
+    components
+}
+
+/// A neighbor closure for searching `grid`, moving orthogonally into any cell
+/// that `passable` accepts.
+pub fn steps<'a, T, G>(
+    grid: &'a G,
+    passable: impl Fn(&T) -> bool + 'a,
+) -> impl Fn(&Point) -> Vec<Point> + 'a
+where
+    G: GridLike<T>,
+{
+    move |&pos| {
+        grid.neighbors4(pos)
+            .filter(|&next| grid.get(next).is_some_and(&passable))
+            .collect()
+    }
+}
+
+/// A neighbor closure for searching `grid`, moving orthogonally into any cell
+/// that `cost` gives a cost for.
+pub fn weighted_steps<'a, T, G, C>(
+    grid: &'a G,
+    cost: impl Fn(&T) -> Option<C> + 'a,
+) -> impl Fn(&Point) -> Vec<(Point, C)> + 'a
+where
+    G: GridLike<T>,
+{
+    move |&pos| {
+        grid.neighbors4(pos)
+            .filter_map(|next| Some((next, cost(grid.get(next)?)?)))
+            .collect()
+    }
+}
+
+/// The orthogonally connected regions of equal cells in `grid`.
+pub fn regions<T: PartialEq>(grid: &Grid<T>) -> Vec<HashSet<Point>> {
+    connected_components(grid.positions(), |&pos| {
+        grid.neighbors4(pos)
+            .filter(|&next| grid[next] == grid[pos])
+            .collect::<Vec<_>>()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    pub const MAZE: &str = "\
+S..#....
+.#.#.##.
+.#...#..
+.####.#.
+......#E";
+
+    fn maze() -> (Grid<char>, Point, Point) {
+        let grid = Grid::<char>::parse(MAZE).unwrap();
+        let start = grid.find(|c| *c == 'S').unwrap();
+        let end = grid.find(|c| *c == 'E').unwrap();
+        (grid, start, end)
+    }
+
+    #[test]
+    fn test_bfs() {
+        let (grid, start, end) = maze();
+        let paths = bfs(start, steps(&grid, |c| *c != '#'));
+        assert_eq!(paths.cost(&end), Some(15));
+
+        let path = paths.path_to(&end).unwrap();
+        assert_eq!(path.len(), 16);
+        assert_eq!((path[0], path[15]), (start, end));
+        assert!(path.windows(2).all(|pair| pair[0].manhattan(pair[1]) == 1));
+        assert_eq!(paths.cost(&Point::new(3, 0)), None);
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        // Moving into a digit costs that much, so the cheap way round is longer.
+        let grid = Grid::<u8>::parse("1911\n1911\n1111").unwrap();
+        let end = Point::new(3, 0);
+        let paths = dijkstra(Point::ORIGIN, weighted_steps(&grid, |&n| Some(n as u32)));
+        assert_eq!(paths.cost(&end), Some(7));
+        assert_eq!(paths.path_to(&end).unwrap().len(), 8);
+        assert_eq!(paths.len(), 12);
+    }
+
+    #[test]
+    fn test_astar() {
+        let (grid, start, end) = maze();
+        let neighbors = |pos: &Point| {
+            steps(&grid, |c| *c != '#')(pos)
+                .into_iter()
+                .map(|next| (next, 1))
+        };
+        let (path, cost) = astar(
+            start,
+            neighbors,
+            |pos| pos.manhattan(end),
+            |pos| *pos == end,
+        )
+        .unwrap();
+        assert_eq!(cost, 15);
+        assert_eq!(path.last(), Some(&end));
+
+        assert_eq!(astar(start, neighbors, |_| 0, |pos| pos.x > 10), None);
+    }
+
+    #[test]
+    fn test_astar_inconsistent() {
+        // Never overestimates, but the drop from 4 at 'a' to 0 at 'b' is more
+        // than the edge between them costs, so 'b' is first reached the long way.
+        let edges = |node: &char| match node {
+            's' => vec![('a', 1), ('b', 3)],
+            'a' => vec![('b', 1)],
+            'b' => vec![('g', 3)],
+            _ => vec![],
+        };
+        let heuristic = |node: &char| if *node == 'a' { 4 } else { 0 };
+        let (path, cost) = astar('s', edges, heuristic, |node| *node == 'g').unwrap();
+        assert_eq!(cost, 5);
+        assert_eq!(path, vec!['s', 'a', 'b', 'g']);
+    }
+
+    #[test]
+    fn test_components() {
+        let grid = Grid::<char>::parse("aab\nabb\ncca").unwrap();
+        let mut sizes: Vec<usize> = regions(&grid).iter().map(HashSet::len).collect();
+        sizes.sort();
+        assert_eq!(sizes, vec![1, 2, 3, 3]);
+
+        let evens = flood_fill(0, |&n: &i32| {
+            [n - 2, n + 2].into_iter().filter(|n| n.abs() < 10)
+        });
+        assert_eq!(evens.len(), 9);
+    }
+}
@@ -1,19 +1,33 @@
 use std::time::Instant;
 
-use crate::solns::*;
+use crate::{
+    input,
+    solns::{self, Registration},
+};
+
+/// Run the solution for `day` on its stored input, noting anything that had
+/// to be cleaned up in the input first.
+pub fn run(day: u8, warn_non_ascii: bool) -> Result<(), anyhow::Error> {
+    let solution = match solns::lookup(day) {
+        Registration::Available(solution) => solution,
+        Registration::CompiledOut => {
+            return Err(anyhow::anyhow!(
+                "Day {} was compiled out, rebuild with `--features day{:02}`",
+                day,
+                day
+            ))
+        }
+        Registration::Missing => return Err(anyhow::anyhow!("Day {} not implemented", day)),
+    };
+
+    let (puzzle, changes) = input::load(day)?;
+    for change in changes.report(warn_non_ascii) {
+        eprintln!("Input: {}", change);
+    }
 
-pub fn run(day: u8) -> Result<(), anyhow::Error> {
     let now = Instant::now();
 
-    match day {
-        1 => day_01::run(include_str!("../puzzles/day_01.txt")),
-        2 => day_02::run(include_str!("../puzzles/day_02.txt")),
-        3 => day_03::run(include_str!("../puzzles/day_03.txt")),
-        4 => day_04::run(include_str!("../puzzles/day_04.txt")),
-        5 => day_05::run(include_str!("../puzzles/day_05.txt")),
-        6 => day_06::run(include_str!("../puzzles/day_06.txt")),
-        _ => Err(anyhow::anyhow!("Day {} not implemented", day)),
-    }?;
+    solution(&puzzle)?;
 
     println!("Ran in {:?}", now.elapsed());
 
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use crate::parse::{self, AocParse};
+
 pub fn run(puzzle: &str) -> Result<(), anyhow::Error> {
     println!("Part 1: {}", part1(puzzle)?);
     println!("Part 2: {}", part2(puzzle)?);
@@ -30,17 +32,17 @@ fn part2(puzzle: &str) -> Result<i32, anyhow::Error> {
         .sum())
 }
 
+#[derive(AocParse)]
+#[aoc(pattern = "{left}   {right}")]
+struct Pair {
+    left: i32,
+    right: i32,
+}
+
 fn collect_cols(puzzle: &str) -> anyhow::Result<(Vec<i32>, Vec<i32>)> {
-    puzzle
-        .lines()
-        .map(|line| {
-            let (left, right) = line
-                .split_once("   ")
-                .ok_or_else(|| anyhow::anyhow!("Invalid line"))?;
-            Ok((left.parse::<i32>()?, right.parse::<i32>()?))
-        })
-        .collect::<Result<Vec<(i32, i32)>, _>>()
-        .map(|pairs| pairs.into_iter().unzip())
+    let pairs: Vec<Pair> = parse::lines(puzzle, str::parse)?;
+
+    Ok(pairs.into_iter().map(|pair| (pair.left, pair.right)).unzip())
 }
 
 #[cfg(test)]
@@ -1,3 +1,7 @@
+use std::ops::RangeInclusive;
+
+use crate::parse;
+
 pub fn run(puzzle: &str) -> anyhow::Result<()> {
     println!("Part 1: {}", part1(puzzle)?);
     println!("Part 2: {}", part2(puzzle)?);
@@ -6,78 +10,97 @@ pub fn run(puzzle: &str) -> anyhow::Result<()> {
 }
 
 fn part1(puzzle: &str) -> anyhow::Result<i32> {
-    Ok(parse(puzzle)?
-        .iter()
-        .map(|report| is_report_safe(report) as i32)
-        .sum())
+    count_safe(puzzle, &SafetyPolicy::STRICT)
 }
 
 fn part2(puzzle: &str) -> anyhow::Result<i32> {
+    count_safe(puzzle, &SafetyPolicy::DAMPENED)
+}
+
+/// How many of the reports in `puzzle` are safe under `policy`.
+pub fn count_safe(puzzle: &str, policy: &SafetyPolicy) -> anyhow::Result<i32> {
     Ok(parse(puzzle)?
         .iter()
-        .map(|report| {
-            if is_report_safe(report) {
-                return 1;
-            }
-
-            for idx in 0..report.len() {
-                if is_report_safe(&report_with_level_removed(report, idx)) {
-                    return 1;
-                }
-            }
-
-            return 0;
-        })
-        .sum())
+        .filter(|report| policy.is_safe(report))
+        .count() as i32)
 }
 
 fn parse(puzzle: &str) -> anyhow::Result<Vec<Vec<i32>>> {
-    puzzle
-        .lines()
-        .map(|line| {
-            line.split_whitespace()
-                .map(str::parse)
-                .collect::<Result<Vec<i32>, _>>()
-        })
-        .collect::<Result<Vec<Vec<i32>>, _>>()
-        .map_err(|e| anyhow::anyhow!(e))
+    Ok(parse::lines(puzzle, parse::words)?)
 }
 
-fn is_report_safe(report: &Vec<i32>) -> bool {
-    let (all_positive, all_negative, all_in_range) = report
-        // Find the difference between each pair.
-        .windows(2)
-        .map(|w| w[0] - w[1])
-        // Map every difference to a tuple of booleans (is_pos, is_neg, is_in_range).
-        .map(|diff| {
-            (
-                diff > 0,                           // Is positive
-                diff < 0,                           // Is negative
-                diff.abs() >= 1 && diff.abs() <= 3, // Is in range
-            )
-        })
-        // Fold map to check (are_all_positive, are_all_negative, are_all_in_range).
-        .fold((true, true, true), |acc, (is_pos, is_neg, is_in_range)| {
-            (
-                acc.0 && is_pos,      // All are positive
-                acc.1 && is_neg,      // All are negative
-                acc.2 && is_in_range, // All are in range
-            )
-        });
-
-    (all_positive || all_negative) && all_in_range
+/// The rules a report has to follow to be safe.
+#[derive(Debug, Clone)]
+pub struct SafetyPolicy {
+    /// How far apart neighbouring levels may be.
+    pub steps: RangeInclusive<i32>,
+    /// Whether the levels must all increase or all decrease.
+    pub monotonic: bool,
+    /// How many levels the dampener may remove.
+    pub removals: usize,
 }
 
-fn report_with_level_removed(report: &[i32], idx: usize) -> Vec<i32> {
-    report
-        .iter()
-        .enumerate()
-        .filter_map(|(i, &val)| if i == idx { None } else { Some(val) })
-        .collect()
+impl SafetyPolicy {
+    /// The rules for part 1: steps of 1 to 3 in one direction, with no
+    /// levels removed.
+    pub const STRICT: Self = Self {
+        steps: 1..=3,
+        monotonic: true,
+        removals: 0,
+    };
+
+    /// The rules for part 2, where the dampener may remove one level.
+    pub const DAMPENED: Self = Self {
+        removals: 1,
+        ..Self::STRICT
+    };
+
+    /// Whether `report` follows the rules once at most `removals` levels are
+    /// taken out.
+    pub fn is_safe(&self, report: &[i32]) -> bool {
+        let trends: &[i32] = if self.monotonic { &[1, -1] } else { &[0] };
+        trends
+            .iter()
+            .any(|&trend| self.fewest_removals(report, trend) <= self.removals)
+    }
+
+    /// The fewest levels to take out of `report` so that every step left
+    /// follows `trend`: 1 for rising, -1 for falling or 0 for either.
+    ///
+    /// This is a single pass over the report, looking back at most
+    /// `removals + 1` levels from each one, so the answer is only exact when
+    /// it is within `removals`.
+    fn fewest_removals(&self, report: &[i32], trend: i32) -> usize {
+        let fits = |from: i32, to: i32| {
+            let step = if trend == 0 {
+                (to - from).abs()
+            } else {
+                (to - from) * trend
+            };
+            self.steps.contains(&step)
+        };
+
+        // `fewest[i]` is the fewest removals before level `i` that leave a
+        // safe run of levels ending with it.
+        let mut fewest = Vec::with_capacity(report.len());
+        let mut best = report.len();
+        for (i, &level) in report.iter().enumerate() {
+            let removals = (i.saturating_sub(self.removals + 1)..i)
+                .filter(|&j| fits(report[j], level))
+                .map(|j| fewest[j] + (i - j - 1))
+                .fold(i, usize::min);
+            fewest.push(removals);
+            best = best.min(removals + (report.len() - 1 - i));
+        }
+
+        best
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use proptest::prelude::*;
+
     use super::*;
 
     pub const EXAMPLE: &str = "7 6 4 2 1
@@ -96,4 +119,54 @@ mod test {
     fn test_part2() {
         assert_eq!(part2(EXAMPLE).unwrap(), 4);
     }
+
+    #[test]
+    fn test_policy() {
+        let loose = SafetyPolicy {
+            steps: 1..=5,
+            monotonic: false,
+            removals: 2,
+        };
+        assert_eq!(count_safe(EXAMPLE, &loose).unwrap(), 6);
+
+        let level = SafetyPolicy {
+            steps: 0..=2,
+            ..SafetyPolicy::STRICT
+        };
+        assert!(level.is_safe(&[8, 6, 4, 4, 3]));
+        assert!(!level.is_safe(&[1, 3, 2, 4, 5]));
+    }
+
+    /// Whether `report` is safe with some choice of up to `removals` levels
+    /// taken out, trying every one.
+    fn is_safe_brute_force(policy: &SafetyPolicy, report: &[i32], removals: usize) -> bool {
+        let is_safe = SafetyPolicy {
+            removals: 0,
+            ..policy.clone()
+        }
+        .is_safe(report);
+
+        is_safe
+            || (removals > 0
+                && (0..report.len()).any(|idx| {
+                    let mut removed = report.to_vec();
+                    removed.remove(idx);
+                    is_safe_brute_force(policy, &removed, removals - 1)
+                }))
+    }
+
+    proptest! {
+        #[test]
+        fn prop_matches_brute_force(
+            report in prop::collection::vec(0i32..8, 0..8),
+            removals in 0usize..3,
+            monotonic: bool,
+        ) {
+            let policy = SafetyPolicy { steps: 1..=2, monotonic, removals };
+            prop_assert_eq!(
+                policy.is_safe(&report),
+                is_safe_brute_force(&policy, &report, removals)
+            );
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use regex::Regex;
+use std::io::{self, ErrorKind, Read};
 
 pub fn run(puzzle: &str) -> anyhow::Result<()> {
     println!("Part 1: {}", part1(puzzle)?);
@@ -7,64 +7,193 @@ pub fn run(puzzle: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn part1(puzzle: &str) -> anyhow::Result<i32> {
-    let re = Regex::new(r"mul\(([0-9]*),([0-9]*)\)")?;
+fn part1(puzzle: &str) -> anyhow::Result<u64> {
+    Ok(sum_reader(puzzle.as_bytes(), false)?)
+}
 
-    let mut total = 0;
-    for (_, [a, b]) in re.captures_iter(puzzle).map(|c| c.extract()) {
-        total = total + a.parse::<i32>()? * b.parse::<i32>()?;
-    }
+fn part2(puzzle: &str) -> anyhow::Result<u64> {
+    Ok(sum_reader(puzzle.as_bytes(), true)?)
+}
+
+/// One instruction found in the corrupted memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Mul(u32, u32),
+    Do,
+    Dont,
+}
+
+const MUL: &[u8] = b"mul(";
+const DO: &[u8] = b"do()";
+const DONT: &[u8] = b"don't()";
+
+/// How much of an instruction the scanner has read so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum State {
+    #[default]
+    Start,
+    /// The first `n` bytes of `mul(`.
+    Mul(usize),
+    /// Up to three digits of the first operand.
+    Left { value: u32, digits: u8 },
+    /// Up to three digits of the second operand, after the comma.
+    Right { left: u32, value: u32, digits: u8 },
+    /// The first `n` bytes of `do()`.
+    Do(usize),
+    /// The first `n` bytes of `don't()`.
+    Dont(usize),
+}
+
+enum Step {
+    Partial(State),
+    Done(Instruction),
+}
 
-    Ok(total)
+/// Finds instructions in corrupted memory a byte at a time, so it can be fed
+/// the memory in chunks of any size without allocating.
+#[derive(Debug, Clone, Default)]
+pub struct Scanner {
+    state: State,
 }
 
-fn part2(puzzle: &str) -> anyhow::Result<i32> {
-    let re = Regex::new(r"mul\(([0-9]*),([0-9]*)\)|do\(\)|don't\(\)")?;
-
-    let mut total = 0;
-    let mut is_active = true;
-    for cap in re.captures_iter(puzzle) {
-        match parse_op(&cap.get(0).ok_or(anyhow::anyhow!("No match found."))?)? {
-            Operation::Do => is_active = true,
-            Operation::Dont => is_active = false,
-            Operation::Mul => {
-                let a = cap
-                    .get(1)
-                    .ok_or(anyhow::anyhow!("Multiply op does not have first value."))?
-                    .as_str();
-                let b = cap
-                    .get(2)
-                    .ok_or(anyhow::anyhow!("Multiple op does not have second value."))?
-                    .as_str();
-                if is_active {
-                    total = total + a.parse::<i32>()? * b.parse::<i32>()?;
-                }
+impl Scanner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read one byte, returning the instruction it completes, if any.
+    pub fn push(&mut self, byte: u8) -> Option<Instruction> {
+        // A byte that breaks an instruction might still start the next one.
+        let step = Self::step(self.state, byte).or_else(|| match self.state {
+            State::Start => None,
+            _ => Self::step(State::Start, byte),
+        });
+
+        match step {
+            Some(Step::Partial(state)) => {
+                self.state = state;
+                None
+            }
+            Some(Step::Done(instruction)) => {
+                self.state = State::Start;
+                Some(instruction)
+            }
+            None => {
+                self.state = State::Start;
+                None
             }
         }
     }
 
-    Ok(total)
+    /// The instructions completed within `bytes`, carrying on from wherever
+    /// the last chunk left off.
+    pub fn feed<'a>(&'a mut self, bytes: &'a [u8]) -> impl Iterator<Item = Instruction> + 'a {
+        bytes.iter().filter_map(|&byte| self.push(byte))
+    }
+
+    fn step(state: State, byte: u8) -> Option<Step> {
+        let digit = byte.is_ascii_digit().then(|| (byte - b'0') as u32);
+        let step = match (state, byte) {
+            (State::Start, b'm') => Step::Partial(State::Mul(1)),
+            (State::Start, b'd') => Step::Partial(State::Do(1)),
+
+            (State::Mul(n), _) if MUL[n] == byte => match n + 1 {
+                len if len == MUL.len() => Step::Partial(State::Left {
+                    value: 0,
+                    digits: 0,
+                }),
+                len => Step::Partial(State::Mul(len)),
+            },
+            (State::Left { value, digits }, _) if digits < 3 && digit.is_some() => {
+                Step::Partial(State::Left {
+                    value: value * 10 + digit?,
+                    digits: digits + 1,
+                })
+            }
+            (State::Left { value, digits }, b',') if digits > 0 => Step::Partial(State::Right {
+                left: value,
+                value: 0,
+                digits: 0,
+            }),
+            (State::Right { left, value, digits }, _) if digits < 3 && digit.is_some() => {
+                Step::Partial(State::Right {
+                    left,
+                    value: value * 10 + digit?,
+                    digits: digits + 1,
+                })
+            }
+            (State::Right { left, value, digits }, b')') if digits > 0 => {
+                Step::Done(Instruction::Mul(left, value))
+            }
+
+            // `do()` and `don't()` only part ways after the `do`.
+            (State::Do(2), b'n') => Step::Partial(State::Dont(3)),
+            (State::Do(n), _) if DO[n] == byte => match n + 1 {
+                len if len == DO.len() => Step::Done(Instruction::Do),
+                len => Step::Partial(State::Do(len)),
+            },
+            (State::Dont(n), _) if DONT[n] == byte => match n + 1 {
+                len if len == DONT.len() => Step::Done(Instruction::Dont),
+                len => Step::Partial(State::Dont(len)),
+            },
+
+            _ => return None,
+        };
+
+        Some(step)
+    }
 }
 
-enum Operation {
-    Do,
-    Dont,
-    Mul,
+/// Runs instructions as they are scanned, keeping a running total of the
+/// products.
+#[derive(Debug, Clone)]
+pub struct Program {
+    scanner: Scanner,
+    /// Whether `do()` and `don't()` switch the `mul`s on and off.
+    conditionals: bool,
+    enabled: bool,
+    total: u64,
 }
 
-fn parse_op(raw_op: &regex::Match) -> anyhow::Result<Operation> {
-    if raw_op.as_str() == "do()" {
-        return Ok(Operation::Do);
-    } else if raw_op.as_str() == "don't()" {
-        return Ok(Operation::Dont);
-    } else if raw_op.as_str().contains("mul") {
-        return Ok(Operation::Mul);
+impl Program {
+    pub fn new(conditionals: bool) -> Self {
+        Self {
+            scanner: Scanner::new(),
+            conditionals,
+            enabled: true,
+            total: 0,
+        }
     }
 
-    return Err(anyhow::anyhow!(
-        "Operation cannot be categorized {}",
-        raw_op.as_str()
-    ));
+    pub fn feed(&mut self, bytes: &[u8]) {
+        for instruction in self.scanner.feed(bytes) {
+            match instruction {
+                Instruction::Mul(a, b) if self.enabled => self.total += a as u64 * b as u64,
+                Instruction::Mul(..) => {}
+                Instruction::Do => self.enabled = true,
+                Instruction::Dont => self.enabled = !self.conditionals,
+            }
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+}
+
+/// The total of the `mul`s in corrupted memory read from `reader`, a buffer
+/// at a time.
+pub fn sum_reader(mut reader: impl Read, conditionals: bool) -> io::Result<u64> {
+    let mut program = Program::new(conditionals);
+    let mut buffer = [0; 8192];
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => return Ok(program.total()),
+            Ok(len) => program.feed(&buffer[..len]),
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -86,4 +215,35 @@ mod test {
     fn test_part2() {
         assert_eq!(part2(EXAMPLE_2).unwrap(), 48);
     }
+
+    #[test]
+    fn test_scanner() {
+        let mut scanner = Scanner::new();
+        let found: Vec<_> = scanner
+            .feed(b"mmul(1,2)mul(,5)mul(1234,5)mul(12,345)domul(3,4)don't(don't()do()")
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                Instruction::Mul(1, 2),
+                Instruction::Mul(12, 345),
+                Instruction::Mul(3, 4),
+                Instruction::Dont,
+                Instruction::Do,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chunks() {
+        // Splitting the memory anywhere, even mid instruction, changes nothing.
+        let memory = EXAMPLE_2.as_bytes();
+        for size in 1..memory.len() {
+            let mut program = Program::new(true);
+            for chunk in memory.chunks(size) {
+                program.feed(chunk);
+            }
+            assert_eq!(program.total(), 48);
+        }
+    }
 }
@@ -1,4 +1,7 @@
-use std::{cmp::Ordering, collections::HashMap};
+use crate::{
+    graph::{Cycle, Graph},
+    parse::{self, AocParse},
+};
 
 pub fn run(puzzle: &str) -> anyhow::Result<()> {
     println!("Part 1: {}", part1(puzzle)?);
@@ -25,89 +28,78 @@ fn part1(puzzle: &str) -> anyhow::Result<i32> {
 fn part2(puzzle: &str) -> anyhow::Result<i32> {
     let (rules, updates) = parse(puzzle)?;
 
-    Ok(updates
-        .into_iter()
-        .filter_map(|update| {
-            if !is_update_valid(&rules, &update) {
-                Some(middle(fix_page(&rules, &update)))
-            } else {
-                None
-            }
-        })
-        .sum())
+    let mut total = 0;
+    for update in updates {
+        if !is_update_valid(&rules, &update) {
+            total += middle(fix_page(&rules, &update)?);
+        }
+    }
+
+    Ok(total)
 }
 
-fn parse(puzzle: &str) -> anyhow::Result<(HashMap<i32, Vec<i32>>, Vec<Vec<i32>>)> {
-    let mut rules: HashMap<i32, Vec<i32>> = HashMap::new();
-    let mut updates = vec![];
-
-    for line in puzzle.lines() {
-        if line.is_empty() {
-            continue;
-        } else if let Some((first, second)) = parse_rule(line) {
-            // Handle the rule lines.
-            rules
-                .entry(first)
-                .and_modify(|following| following.push(second))
-                .or_insert(vec![second]);
-        } else {
-            // Handle the update lines.
-            updates.push(
-                line.split(",")
-                    .map(|x| Ok(x.parse::<i32>()?))
-                    .collect::<anyhow::Result<Vec<i32>>>()?,
-            );
-        };
-    }
+/// An edge from each page to every page that must come after it.
+type Rules = Graph<i32>;
 
-    Ok((rules, updates))
+#[derive(AocParse)]
+#[aoc(pattern = "{before}|{after}")]
+struct Rule {
+    before: i32,
+    after: i32,
 }
 
-fn parse_rule(rule: &str) -> Option<(i32, i32)> {
-    rule.split_once("|")
-        .map(|(a, b)| (a.parse().unwrap(), b.parse().unwrap()))
+#[derive(AocParse)]
+#[aoc(pattern = "{pages}")]
+struct Update {
+    #[aoc(sep = ",")]
+    pages: Vec<i32>,
 }
 
-fn is_update_valid(rules: &HashMap<i32, Vec<i32>>, update: &Vec<i32>) -> bool {
-    let mut current = update[0];
-    let mut preceeding = &update[0..0];
-    let mut following = &update[1..];
-
-    while preceeding.len() < update.len() {
-        if let Some(following_rules) = rules.get(&current) {
-            // The update is not valid if the values preceeding the current one
-            // include a value that the rule deems must follow the value.
-            if following_rules.iter().any(|rule| preceeding.contains(rule)) {
-                return false;
-            }
-        }
+fn parse(puzzle: &str) -> anyhow::Result<(Rules, Vec<Vec<i32>>)> {
+    let [rule_lines, update_lines] = parse::sections(puzzle)[..] else {
+        return Err(anyhow::anyhow!("Expected a section of rules and a section of updates"));
+    };
 
-        // Shift forward to check the next value.
-        preceeding = &update[0..(preceeding.len() + 1)];
-        if let Some(page) = following.get(0) {
-            current = *page;
-            following = &following[1..];
-        }
-    }
+    let rules = rule_lines
+        .lines(str::parse)?
+        .into_iter()
+        .map(|Rule { before, after }| (before, after))
+        .collect();
+
+    let updates = update_lines
+        .lines(str::parse)?
+        .into_iter()
+        .map(|update: Update| update.pages)
+        .collect();
 
-    true
+    Ok((rules, updates))
+}
+
+fn is_update_valid(rules: &Rules, update: &[i32]) -> bool {
+    // The update is not valid if any page must come before one earlier on.
+    update
+        .iter()
+        .enumerate()
+        .all(|(idx, page)| !update[idx + 1..].iter().any(|later| rules.has_edge(later, page)))
 }
 
 fn middle(page: Vec<i32>) -> i32 {
     page[page.len() / 2]
 }
 
-fn fix_page(rules: &HashMap<i32, Vec<i32>>, update: &Vec<i32>) -> Vec<i32> {
-    let mut fixed_update = update.clone();
-    fixed_update.sort_by(|a, b| {
-        if rules.get(a).is_some_and(|pages| pages.contains(b)) {
-            Ordering::Less
-        } else {
-            Ordering::Greater
-        }
-    });
+/// Order the pages of `update` by the rules between them, which only needs
+/// those rules to be acyclic, not the whole set.
+fn fix_page(rules: &Rules, update: &[i32]) -> Result<Vec<i32>, Cycle<i32>> {
+    let order = rules.induced(update.iter().copied()).topological_sort()?;
 
-    fixed_update
+    // The graph holds each page once, so put back any that were repeated.
+    Ok(order
+        .into_iter()
+        .flat_map(|page| {
+            let count = update.iter().filter(|&&other| other == page).count();
+            std::iter::repeat_n(page, count)
+        })
+        .collect())
 }
 
 #[cfg(test)]
@@ -152,4 +144,21 @@ mod test {
     fn test_part2() {
         assert_eq!(part2(EXAMPLE).unwrap(), 123);
     }
+
+    #[test]
+    fn test_repeated_pages() {
+        let rules = [(1, 2), (3, 2)].into_iter().collect();
+        assert_eq!(fix_page(&rules, &[2, 1, 2, 3]).unwrap(), vec![1, 3, 2, 2]);
+        assert_eq!(part2("1|2\n\n2,1,2").unwrap(), 2);
+    }
+
+    #[test]
+    fn test_cycle() {
+        let puzzle = "1|2\n2|3\n3|1\n\n1,2\n3,2,1";
+        assert_eq!(part1(puzzle).unwrap(), 2);
+        assert_eq!(
+            part2(puzzle).unwrap_err().to_string(),
+            "Found a cycle: 3 -> 1 -> 2 -> 3"
+        );
+    }
 }
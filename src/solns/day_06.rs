@@ -1,3 +1,8 @@
+use crate::{
+    geometry::{Direction, Point},
+    grid::{FromChar, Grid, Render, ToChar},
+    parse,
+};
 use std::{collections::HashSet, io::Write, thread::sleep, time::Duration};
 
 pub fn run(puzzle: &str) -> anyhow::Result<()> {
@@ -11,7 +16,7 @@ fn part1(puzzle: &str) -> anyhow::Result<i32> {
     let (map, guard_start) = parse(puzzle)?;
     let mut guard = Guard::new(guard_start);
 
-    let mut visited: HashSet<Pos> = HashSet::new();
+    let mut visited: HashSet<Point> = HashSet::new();
     while guard.is_within_map(&map) {
         visited.insert(guard.pos);
         guard.step(&map);
@@ -22,77 +27,231 @@ fn part1(puzzle: &str) -> anyhow::Result<i32> {
 
 fn part2(puzzle: &str) -> anyhow::Result<i32> {
     let (map, guard_start) = parse(puzzle)?;
+    let jumps = Jumps::new(&map);
+    let candidates = candidates(&map, guard_start);
+
+    // Each candidate is independent, so split them evenly between threads.
+    let threads = std::thread::available_parallelism().map_or(1, usize::from);
+    let chunk_len = candidates.len().div_ceil(threads).max(1);
+    let loops = std::thread::scope(|scope| {
+        let workers: Vec<_> = candidates
+            .chunks(chunk_len)
+            .map(|chunk| {
+                let jumps = &jumps;
+                scope.spawn(move || {
+                    let mut seen = Visited::new(jumps.cells() * 4);
+                    chunk
+                        .iter()
+                        .filter(|candidate| jumps.loops(candidate, &mut seen))
+                        .count()
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .map(|worker| worker.join().expect("a worker thread panicked"))
+            .sum::<usize>()
+    });
+
+    Ok(loops as i32)
+}
+
+/// Somewhere to put an obstacle, with where the guard is the first time it
+/// is about to walk into it.
+///
+/// The path up to that point is the same with or without the obstacle, so
+/// the guard can be started from there.
+#[derive(Debug, Clone, Copy)]
+struct Candidate {
+    obstacle: Point,
+    from: Point,
+    dir: Direction,
+}
+
+/// Every cell on the guard's original path other than the start, in the
+/// order they are first reached.
+fn candidates(map: &Grid<Tile>, guard_start: Point) -> Vec<Candidate> {
     let mut guard = Guard::new(guard_start);
+    let mut reached = vec![false; map.width() * map.height()];
+    reached[flat_index(map, guard_start)] = true;
+
+    let mut candidates = vec![];
+    loop {
+        let from = guard.pos;
+        guard.step(map);
+        if !guard.is_within_map(map) {
+            break;
+        }
 
-    // Get all visited locations. These are places we could place an obstacle.
-    let mut visited: HashSet<Pos> = HashSet::new();
-    while guard.is_within_map(&map) {
-        visited.insert(guard.pos);
-        guard.step(&map);
+        let idx = flat_index(map, guard.pos);
+        if !reached[idx] {
+            reached[idx] = true;
+            candidates.push(Candidate {
+                obstacle: guard.pos,
+                from,
+                dir: guard.dir,
+            });
+        }
     }
 
-    let mut loops = 0;
-    for pos in visited.iter() {
-        guard.reset();
-        if is_inf_loop(&mut guard, &map_with_obstacle(map.clone(), pos)?) {
-            loops += 1;
+    candidates
+}
+
+fn flat_index(map: &Grid<Tile>, pos: Point) -> usize {
+    pos.to_flat_index(map.width(), map.height())
+        .expect("position is within the map")
+}
+
+/// For every cell and direction, the cell the guard stops in when walking
+/// that way before bumping into an obstacle.
+struct Jumps {
+    width: usize,
+    height: usize,
+    stops: Vec<u32>,
+}
+
+impl Jumps {
+    /// Marks walking off the edge of the map rather than stopping.
+    const EXIT: u32 = u32::MAX;
+
+    fn new(map: &Grid<Tile>) -> Self {
+        let (width, height) = (map.width(), map.height());
+        let mut stops = vec![Self::EXIT; width * height * 4];
+
+        // Each stop depends on the one a step ahead, so fill the table in
+        // from the far side for each direction.
+        let positions: Vec<Point> = map.positions().collect();
+        for dir in Direction::ALL {
+            let order: Box<dyn Iterator<Item = &Point>> = match dir {
+                Direction::Up | Direction::Left => Box::new(positions.iter()),
+                Direction::Down | Direction::Right => Box::new(positions.iter().rev()),
+            };
+            for &pos in order {
+                let ahead = pos + dir.delta();
+                let idx = flat_index(map, pos);
+                stops[idx * 4 + dir as usize] = match map.get(ahead) {
+                    None => Self::EXIT,
+                    Some(Tile::Obstacle) => idx as u32,
+                    Some(Tile::Open) => stops[flat_index(map, ahead) * 4 + dir as usize],
+                };
+            }
+        }
+
+        Self {
+            width,
+            height,
+            stops,
         }
     }
 
-    Ok(loops)
-}
+    fn cells(&self) -> usize {
+        self.width * self.height
+    }
+
+    fn index(&self, pos: Point) -> usize {
+        pos.to_flat_index(self.width, self.height)
+            .expect("position is within the map")
+    }
+
+    fn point(&self, idx: usize) -> Point {
+        Point::from((idx % self.width, idx / self.width))
+    }
+
+    /// Where the guard stops walking from `pos` towards `dir` with an extra
+    /// obstacle at `obstacle`, or `None` if they walk off the map.
+    fn stop(&self, pos: Point, dir: Direction, obstacle: Point) -> Option<Point> {
+        let stop = self.stops[self.index(pos) * 4 + dir as usize];
+        let stop = (stop != Self::EXIT).then(|| self.point(stop as usize));
+
+        // The extra obstacle only matters if it is ahead of the guard and
+        // before wherever they would have stopped anyway.
+        let delta = dir.delta();
+        let to_obstacle = obstacle - pos;
+        let ahead = to_obstacle.x * delta.y == to_obstacle.y * delta.x
+            && to_obstacle.x * delta.x + to_obstacle.y * delta.y > 0;
+        let blocks = ahead && stop.is_none_or(|stop| obstacle.manhattan(pos) <= stop.manhattan(pos));
+
+        if blocks {
+            Some(obstacle - delta)
+        } else {
+            stop
+        }
+    }
 
-fn map_with_obstacle(map: Vec<Vec<bool>>, obs: &Pos) -> anyhow::Result<Vec<Vec<bool>>> {
-    let mut with_obstacle = map;
-    with_obstacle[obs.1 as usize][obs.0 as usize] = true;
+    /// Whether putting the candidate's obstacle down traps the guard in a
+    /// loop, using `seen` to track the stops visited.
+    fn loops(&self, candidate: &Candidate, seen: &mut Visited) -> bool {
+        seen.clear();
+        let (mut pos, mut dir) = (candidate.from, candidate.dir);
+        while let Some(stop) = self.stop(pos, dir, candidate.obstacle) {
+            if !seen.insert(self.index(stop) * 4 + dir as usize) {
+                return true;
+            }
+            pos = stop;
+            dir = dir.turn_right();
+        }
 
-    Ok(with_obstacle)
+        false
+    }
 }
 
-fn is_inf_loop(guard: &mut Guard, map: &Vec<Vec<bool>>) -> bool {
-    let mut visited_with_dir: HashSet<(Pos, Dir)> = HashSet::new();
+/// A fixed size bitset that remembers which words it touched, so clearing it
+/// between candidates only costs as much as the candidate used.
+struct Visited {
+    bits: Vec<u64>,
+    touched: Vec<usize>,
+}
 
-    // If the guard leaves the map, we cannot have an infinite loop...
-    while guard.is_within_map(map) {
-        // and if the guard returns to a position it has been in before
-        // while pointing in the same direction, we must have an infinite loop.
-        if visited_with_dir.contains(&(guard.pos, guard.dir)) {
-            return true;
+impl Visited {
+    fn new(len: usize) -> Self {
+        Self {
+            bits: vec![0; len.div_ceil(64)],
+            touched: vec![],
         }
+    }
 
-        visited_with_dir.insert((guard.pos, guard.dir));
-        guard.step(&map);
+    /// Set bit `idx`, returning whether it was previously unset.
+    fn insert(&mut self, idx: usize) -> bool {
+        let (word, mask) = (idx / 64, 1 << (idx % 64));
+        if self.bits[word] & mask != 0 {
+            return false;
+        }
+        if self.bits[word] == 0 {
+            self.touched.push(word);
+        }
+        self.bits[word] |= mask;
+
+        true
     }
 
-    return false;
+    fn clear(&mut self) {
+        for word in self.touched.drain(..) {
+            self.bits[word] = 0;
+        }
+    }
 }
 
 #[allow(dead_code)]
-fn render(map: &Vec<Vec<bool>>, guard: &Guard, visited: &HashSet<Pos>) {
+fn render(map: &Grid<Tile>, guard: &Guard, visited: &HashSet<Point>) {
     let mut buffer = String::new();
 
     // Move the cursor to the top-left corner without clearing the screen
     buffer.push_str("\x1B[H");
 
-    for (y, row) in map.iter().enumerate() {
-        for (x, is_blocked) in row.into_iter().enumerate() {
-            if guard.pos.0 == x as i32 && guard.pos.1 == y as i32 {
-                match guard.dir {
-                    Dir::Up => buffer.push('^'),
-                    Dir::Right => buffer.push('>'),
-                    Dir::Down => buffer.push('V'),
-                    Dir::Left => buffer.push('<'),
-                }
-            } else if visited.contains(&(x.try_into().unwrap(), y.try_into().unwrap())) {
-                buffer.push('X');
-            } else if is_blocked == &true {
-                buffer.push('#');
-            } else {
-                buffer.push('.');
-            }
-        }
-        buffer.push('\n');
-    }
+    let guard_char = match guard.dir {
+        Direction::Up => '^',
+        Direction::Right => '>',
+        Direction::Down => 'V',
+        Direction::Left => '<',
+    };
+    buffer.push_str(
+        &Render::default()
+            .overlay(visited.iter().copied(), 'X')
+            .marker(guard.pos, guard_char)
+            .grid(map),
+    );
+    buffer.push('\n');
 
     // Write the buffer to the terminal in one go
     print!("{}", buffer);
@@ -102,110 +261,68 @@ fn render(map: &Vec<Vec<bool>>, guard: &Guard, visited: &HashSet<Pos>) {
     sleep(Duration::from_millis(10));
 }
 
-type Pos = (i32, i32);
-
-#[derive(Hash, Debug, Eq, PartialEq, Copy, Clone)]
-enum Dir {
-    Up,
-    Left,
-    Right,
-    Down,
-}
-
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct Guard {
-    start: Pos,
-    pos: Pos,
-    dir: Dir,
+    pos: Point,
+    dir: Direction,
 }
 
 impl Guard {
-    fn new(origin: Pos) -> Self {
+    fn new(origin: Point) -> Self {
         Self {
-            start: origin,
             pos: origin,
-            dir: Dir::Up,
+            dir: Direction::Up,
         }
     }
 
-    fn step(&mut self, map: &Vec<Vec<bool>>) {
-        self.pos = move_forward(self.pos, &self.dir);
-        if self.is_within_map(map) && is_blocked(map, &self.pos) {
-            self.pos = move_backward(self.pos, &self.dir);
-            self.dir = turn_right(&self.dir);
+    fn step(&mut self, map: &Grid<Tile>) {
+        self.pos += self.dir.delta();
+        if is_blocked(map, &self.pos) {
+            self.pos -= self.dir.delta();
+            self.dir = self.dir.turn_right();
             self.step(map);
         }
     }
 
-    fn is_within_map(&self, map: &Vec<Vec<bool>>) -> bool {
-        if self.pos.0 < 0 || self.pos.1 < 0 {
-            return false;
-        }
-        if self.pos.1 >= map.len() as i32 {
-            return false;
-        }
-        if self.pos.0 >= map[0].len() as i32 {
-            return false;
-        }
-
-        return true;
-    }
-
-    fn reset(&mut self) {
-        self.pos = self.start;
-        self.dir = Dir::Up;
+    fn is_within_map(&self, map: &Grid<Tile>) -> bool {
+        map.contains(self.pos)
     }
 }
 
-fn move_forward(pos: Pos, dir: &Dir) -> Pos {
-    match dir {
-        Dir::Up => (pos.0, pos.1 - 1),
-        Dir::Right => (pos.0 + 1, pos.1),
-        Dir::Down => (pos.0, pos.1 + 1),
-        Dir::Left => (pos.0 - 1, pos.1),
-    }
+fn is_blocked(map: &Grid<Tile>, pos: &Point) -> bool {
+    map.get(*pos) == Some(&Tile::Obstacle)
 }
 
-fn move_backward(pos: Pos, dir: &Dir) -> Pos {
-    match dir {
-        Dir::Up => (pos.0, pos.1 + 1),
-        Dir::Right => (pos.0 - 1, pos.1),
-        Dir::Down => (pos.0, pos.1 - 1),
-        Dir::Left => (pos.0 + 1, pos.1),
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tile {
+    Open,
+    Obstacle,
 }
 
-fn turn_right(dir: &Dir) -> Dir {
-    match dir {
-        Dir::Up => Dir::Right,
-        Dir::Right => Dir::Down,
-        Dir::Down => Dir::Left,
-        Dir::Left => Dir::Up,
+impl ToChar for Tile {
+    fn to_char(&self) -> char {
+        match self {
+            Tile::Open => '.',
+            Tile::Obstacle => '#',
+        }
     }
 }
 
-fn is_blocked(map: &Vec<Vec<bool>>, pos: &Pos) -> bool {
-    map[pos.1 as usize][pos.0 as usize]
-}
-
-fn parse(puzzle: &str) -> anyhow::Result<(Vec<Vec<bool>>, Pos)> {
-    let mut map = vec![];
-    let mut guard_start = (0, 0);
-    for (y, line) in puzzle.lines().enumerate() {
-        let mut locs = vec![];
-        for (x, loc) in line.chars().enumerate() {
-            match loc {
-                '.' => locs.push(false),
-                '#' => locs.push(true),
-                '^' => {
-                    guard_start = (x as i32, y as i32);
-                    locs.push(false);
-                }
-                _ => return Err(anyhow::anyhow!("INVALID INPUT!")),
-            }
+impl FromChar for Tile {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '.' => Some(Tile::Open),
+            '#' => Some(Tile::Obstacle),
+            _ => None,
         }
-        map.push(locs);
     }
+}
+
+fn parse(puzzle: &str) -> anyhow::Result<(Grid<Tile>, Point)> {
+    let (map, markers) = parse::grid_with_markers(puzzle, &[('^', Tile::Open)])?;
+    let guard_start = markers
+        .first('^')
+        .ok_or_else(|| anyhow::anyhow!("The map has no guard on it"))?;
 
     Ok((map, guard_start))
 }
@@ -213,6 +330,7 @@ fn parse(puzzle: &str) -> anyhow::Result<(Vec<Vec<bool>>, Pos)> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::cycle;
 
     pub const EXAMPLE: &str = "....#.....
 .........#
@@ -234,4 +352,36 @@ mod test {
     fn test_part2() {
         assert_eq!(part2(EXAMPLE).unwrap(), 6);
     }
+
+    /// The straightforward way to check an obstacle: walk the guard one step
+    /// at a time on a copy of the map until they leave or repeat themselves.
+    fn is_inf_loop(guard: &Guard, map: &Grid<Tile>) -> bool {
+        let outcome = cycle::find_cycle(guard.clone(), |guard| {
+            let mut next = guard.clone();
+            next.step(map);
+            next.is_within_map(map).then_some(next)
+        });
+
+        outcome.is_cycle()
+    }
+
+    #[test]
+    fn test_jumps_match_walking() {
+        let (map, guard_start) = parse(EXAMPLE).unwrap();
+        let jumps = Jumps::new(&map);
+        let mut seen = Visited::new(jumps.cells() * 4);
+        let candidates = candidates(&map, guard_start);
+        assert_eq!(candidates.len(), 40);
+
+        for candidate in candidates {
+            let mut with_obstacle = map.clone();
+            with_obstacle[candidate.obstacle] = Tile::Obstacle;
+            assert_eq!(
+                jumps.loops(&candidate, &mut seen),
+                is_inf_loop(&Guard::new(guard_start), &with_obstacle),
+                "obstacle at {:?}",
+                candidate.obstacle
+            );
+        }
+    }
 }
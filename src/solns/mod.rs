@@ -1,38 +1,30 @@
-pub const TEMPLATE: &str = r#"pub fn run(puzzle: &str) -> anyhow::Result<()> {
-    println!("Part 1: {}", part1(puzzle)?);
-    println!("Part 2: {}", part2(puzzle)?);
-
-    Ok(())
-}
-
-fn part1(puzzle: &str) -> anyhow::Result<i32> {
-    Ok(0)
-}
-
-fn part2(puzzle: &str) -> anyhow::Result<i32> {
-    Ok(0)
+// The `day_XX` modules and the `SOLUTIONS` registry are generated by
+// `build.rs` from the solution files in this directory. Days whose feature is
+// disabled are listed without a solution.
+include!(concat!(env!("OUT_DIR"), "/solns.rs"));
+
+pub type Solution = fn(&str) -> anyhow::Result<()>;
+
+/// Whether a solution for a day is available in this build.
+pub enum Registration {
+    Available(Solution),
+    CompiledOut,
+    Missing,
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-
-    pub const EXAMPLE: &str = "";
-
-    #[test]
-    fn test_part1() {
-        assert_eq!(part1(EXAMPLE).unwrap(), -1);
+/// Look up the registration for `day`.
+pub fn lookup(day: u8) -> Registration {
+    match SOLUTIONS.iter().find(|(registered, _)| *registered == day) {
+        Some((_, Some(solution))) => Registration::Available(*solution),
+        Some((_, None)) => Registration::CompiledOut,
+        None => Registration::Missing,
     }
+}
 
-    #[test]
-    fn test_part2() {
-        assert_eq!(part2(EXAMPLE).unwrap(), -1);
+/// Find the solution registered for `day`, if there is one and it was compiled in.
+pub fn get(day: u8) -> Option<Solution> {
+    match lookup(day) {
+        Registration::Available(solution) => Some(solution),
+        _ => None,
     }
-}"#;
-
-pub mod day_01;
-pub mod day_02;
-pub mod day_03;
-pub mod day_04;
-pub mod day_05;
-pub mod day_06;
+}
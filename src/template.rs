@@ -0,0 +1,313 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::Path,
+};
+
+/// The values substituted into a template's `{{placeholder}}`s.
+#[derive(Debug)]
+pub struct Context {
+    pub day: u8,
+    pub year: u16,
+    pub title: String,
+    pub example: String,
+    pub part1: String,
+    pub part2: String,
+}
+
+/// The name of every `{{placeholder}}` a template can use.
+pub const PLACEHOLDERS: [&str; 7] = [
+    "day",
+    "day_padded",
+    "year",
+    "title",
+    "example",
+    "part1",
+    "part2",
+];
+
+impl Context {
+    /// Each of the [`PLACEHOLDERS`] with the value it is replaced by.
+    fn placeholders(&self) -> [(&'static str, String); 7] {
+        [
+            ("day", self.day.to_string()),
+            ("day_padded", format!("{:02}", self.day)),
+            ("year", self.year.to_string()),
+            // The title goes in a `//` comment, so it has to stay on one line.
+            ("title", one_line(&self.title)),
+            // The example is always pasted inside a string literal.
+            ("example", escape(self.example.trim_end())),
+            ("part1", self.part1.clone()),
+            ("part2", self.part2.clone()),
+        ]
+    }
+}
+
+/// Templates that ship with `adv`, selectable by name.
+pub const BUILTIN: [(&str, &str); 3] = [("lines", LINES), ("grid", GRID), ("sections", SECTIONS)];
+
+/// Write a new solution file for `ctx.day` using the template named `name`.
+///
+/// A template in `dir` called `<name>.rs` takes precedence over a built-in of
+/// the same name. Existing solutions are never replaced unless `force` is set.
+pub fn template(name: &str, dir: &Path, ctx: &Context, force: bool) -> anyhow::Result<()> {
+    let template = load(name, dir)?;
+    let path = format!("./src/solns/day_{:02}.rs", ctx.day);
+
+    let mut options = OpenOptions::new();
+    options.write(true);
+    if force {
+        options.create(true).truncate(true);
+    } else {
+        options.create_new(true);
+    }
+
+    let mut file = options.open(&path).map_err(|e| match e.kind() {
+        ErrorKind::AlreadyExists => {
+            anyhow::anyhow!("{} already exists, use --force to overwrite it", path)
+        }
+        _ => e.into(),
+    })?;
+    file.write_all(render(&template, ctx).as_bytes())?;
+
+    println!("Created {} from the {} template", path, name);
+
+    Ok(())
+}
+
+fn load(name: &str, dir: &Path) -> anyhow::Result<String> {
+    let custom = dir.join(format!("{}.rs", name));
+    if custom.is_file() {
+        return Ok(fs::read_to_string(custom)?);
+    }
+
+    BUILTIN
+        .iter()
+        .find(|(builtin, _)| *builtin == name)
+        .map(|(_, template)| template.to_string())
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No template named {} in {} or among the built-ins ({})",
+                name,
+                dir.display(),
+                BUILTIN.map(|(name, _)| name).join(", ")
+            )
+        })
+}
+
+/// Replace the placeholders in `template` in a single pass, so that text
+/// looking like a placeholder inside a value is left as it is.
+fn render(template: &str, ctx: &Context) -> String {
+    let placeholders = ctx.placeholders();
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let placeholder = rest[2..]
+            .split_once("}}")
+            .and_then(|(name, _)| placeholders.iter().find(|(key, _)| *key == name));
+        match placeholder {
+            Some((key, value)) => {
+                rendered.push_str(value);
+                rest = &rest[key.len() + 4..];
+            }
+            // Only skip the first brace, in case the next one starts a placeholder.
+            None => {
+                rendered.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    rendered.push_str(rest);
+
+    rendered
+}
+
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+const LINES: &str = r#"// Advent of Code {{year}}, day {{day}}: {{title}}
+
+pub fn run(puzzle: &str) -> anyhow::Result<()> {
+    println!("Part 1: {}", part1(puzzle)?);
+    println!("Part 2: {}", part2(puzzle)?);
+
+    Ok(())
+}
+
+fn part1(puzzle: &str) -> anyhow::Result<i32> {
+    let _lines = parse(puzzle)?;
+    Ok(0)
+}
+
+fn part2(puzzle: &str) -> anyhow::Result<i32> {
+    let _lines = parse(puzzle)?;
+    Ok(0)
+}
+
+fn parse(puzzle: &str) -> anyhow::Result<Vec<&str>> {
+    Ok(puzzle.lines().collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    pub const EXAMPLE: &str = "{{example}}";
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(EXAMPLE).unwrap(), {{part1}});
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2(EXAMPLE).unwrap(), {{part2}});
+    }
+}
+"#;
+
+const GRID: &str = r#"// Advent of Code {{year}}, day {{day}}: {{title}}
+
+pub fn run(puzzle: &str) -> anyhow::Result<()> {
+    println!("Part 1: {}", part1(puzzle)?);
+    println!("Part 2: {}", part2(puzzle)?);
+
+    Ok(())
+}
+
+fn part1(puzzle: &str) -> anyhow::Result<i32> {
+    let _grid = parse(puzzle)?;
+    Ok(0)
+}
+
+fn part2(puzzle: &str) -> anyhow::Result<i32> {
+    let _grid = parse(puzzle)?;
+    Ok(0)
+}
+
+fn parse(puzzle: &str) -> anyhow::Result<Vec<Vec<char>>> {
+    let grid: Vec<Vec<char>> = puzzle.lines().map(|line| line.chars().collect()).collect();
+    if grid.iter().any(|row| row.len() != grid[0].len()) {
+        return Err(anyhow::anyhow!("Grid rows are not all the same width"));
+    }
+
+    Ok(grid)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    pub const EXAMPLE: &str = "{{example}}";
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(EXAMPLE).unwrap(), {{part1}});
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2(EXAMPLE).unwrap(), {{part2}});
+    }
+}
+"#;
+
+const SECTIONS: &str = r#"// Advent of Code {{year}}, day {{day}}: {{title}}
+
+pub fn run(puzzle: &str) -> anyhow::Result<()> {
+    println!("Part 1: {}", part1(puzzle)?);
+    println!("Part 2: {}", part2(puzzle)?);
+
+    Ok(())
+}
+
+fn part1(puzzle: &str) -> anyhow::Result<i32> {
+    let (_first, _second) = parse(puzzle)?;
+    Ok(0)
+}
+
+fn part2(puzzle: &str) -> anyhow::Result<i32> {
+    let (_first, _second) = parse(puzzle)?;
+    Ok(0)
+}
+
+fn parse(puzzle: &str) -> anyhow::Result<(Vec<&str>, Vec<&str>)> {
+    let (first, second) = puzzle
+        .split_once("\n\n")
+        .ok_or_else(|| anyhow::anyhow!("Expected two sections separated by a blank line"))?;
+
+    Ok((first.lines().collect(), second.lines().collect()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    pub const EXAMPLE: &str = "{{example}}";
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(EXAMPLE).unwrap(), {{part1}});
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2(EXAMPLE).unwrap(), {{part2}});
+    }
+}
+"#;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn context() -> Context {
+        Context {
+            day: 7,
+            year: 2024,
+            title: "Bridge Repair".to_string(),
+            example: "190: 10 \"19\"\n".to_string(),
+            part1: "3749".to_string(),
+            part2: "-1".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let rendered = render("{{year}} {{day}}/{{day_padded}} {{title}}", &context());
+        assert_eq!(rendered, "2024 7/07 Bridge Repair");
+    }
+
+    #[test]
+    fn test_render_single_pass() {
+        let ctx = Context {
+            title: "{{part2}} and\nmore".to_string(),
+            ..context()
+        };
+        assert_eq!(
+            render("// {{title}}\n{{{day}}} {{other}}", &ctx),
+            "// {{part2}} and more\n{7} {{other}}"
+        );
+    }
+
+    #[test]
+    fn test_render_builtin() {
+        let rendered = render(LINES, &context());
+        assert!(rendered.contains(r#"pub const EXAMPLE: &str = "190: 10 \"19\"";"#));
+        assert!(rendered.contains("assert_eq!(part1(EXAMPLE).unwrap(), 3749);"));
+        assert!(!rendered.contains("{{"));
+    }
+
+    #[test]
+    fn test_load_unknown() {
+        assert!(load("nope", Path::new("./templates")).is_err());
+    }
+}
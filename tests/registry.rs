@@ -0,0 +1,66 @@
+use std::path::Path;
+
+use adv::solns;
+
+/// Whether each day's feature is enabled in this build, indexed from day 1.
+const FEATURES: [bool; 25] = [
+    cfg!(feature = "day01"),
+    cfg!(feature = "day02"),
+    cfg!(feature = "day03"),
+    cfg!(feature = "day04"),
+    cfg!(feature = "day05"),
+    cfg!(feature = "day06"),
+    cfg!(feature = "day07"),
+    cfg!(feature = "day08"),
+    cfg!(feature = "day09"),
+    cfg!(feature = "day10"),
+    cfg!(feature = "day11"),
+    cfg!(feature = "day12"),
+    cfg!(feature = "day13"),
+    cfg!(feature = "day14"),
+    cfg!(feature = "day15"),
+    cfg!(feature = "day16"),
+    cfg!(feature = "day17"),
+    cfg!(feature = "day18"),
+    cfg!(feature = "day19"),
+    cfg!(feature = "day20"),
+    cfg!(feature = "day21"),
+    cfg!(feature = "day22"),
+    cfg!(feature = "day23"),
+    cfg!(feature = "day24"),
+    cfg!(feature = "day25"),
+];
+
+fn has_source(day: u8) -> bool {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join(format!("src/solns/day_{:02}.rs", day))
+        .exists()
+}
+
+#[test]
+fn test_registered_days() {
+    let days: Vec<u8> = solns::SOLUTIONS.iter().map(|(day, _)| *day).collect();
+    let expected: Vec<u8> = (1..=25).filter(|&day| has_source(day)).collect();
+    assert_eq!(days, expected);
+
+    let compiled: Vec<u8> = solns::SOLUTIONS
+        .iter()
+        .filter(|(_, run)| run.is_some())
+        .map(|(day, _)| *day)
+        .collect();
+    let enabled: Vec<u8> = expected
+        .into_iter()
+        .filter(|&day| FEATURES[day as usize - 1])
+        .collect();
+    assert_eq!(compiled, enabled);
+}
+
+#[test]
+#[cfg(feature = "day01")]
+fn test_lookup() {
+    use adv::solns::Registration;
+
+    assert!(matches!(solns::lookup(1), Registration::Available(_)));
+    assert!(matches!(solns::lookup(25), Registration::Missing));
+    assert!(solns::get(1).unwrap()("3   4\n4   3").is_ok());
+}